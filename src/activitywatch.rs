@@ -0,0 +1,279 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::day_boundary;
+use crate::storage;
+use crate::time_format::format_duration;
+use crate::TimeBlock;
+
+/// Window events closer together than this are treated as continuous, since
+/// ActivityWatch leaves small gaps between polls
+const MERGE_GAP_SECONDS: i64 = 10;
+
+/// Host name written into exported buckets
+const EXPORT_HOSTNAME: &str = "timesense";
+
+/// The bucket export format of ActivityWatch (`/api/0/export` or a single
+/// bucket exported from the web UI)
+#[derive(Debug, Serialize, Deserialize)]
+struct BucketExport {
+    buckets: BTreeMap<String, Bucket>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Bucket {
+    id: String,
+    #[serde(default)]
+    created: Option<DateTime<Utc>>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(rename = "type")]
+    bucket_type: String,
+    #[serde(default)]
+    client: String,
+    #[serde(default)]
+    hostname: String,
+    #[serde(default)]
+    data: Map<String, Value>,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Event {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<u64>,
+    timestamp: DateTime<Utc>,
+    /// Seconds
+    duration: f64,
+    data: Map<String, Value>,
+}
+
+impl Event {
+    fn start(&self) -> DateTime<Local> {
+        self.timestamp.with_timezone(&Local)
+    }
+
+    fn end(&self) -> DateTime<Local> {
+        self.start() + Duration::milliseconds((self.duration * 1000.0) as i64)
+    }
+
+    fn text(&self, key: &str) -> Option<&str> {
+        self.data.get(key).and_then(Value::as_str)
+    }
+}
+
+/// Reads an ActivityWatch export into blocks: `currentwindow` events give the
+/// application and window title, and `afkstatus` events mark the idle parts.
+/// Blocks are categorized with the current rules but not yet split by day.
+fn read_blocks(config: &Config, path: &Path) -> Result<Vec<TimeBlock>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let export: BucketExport = serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not an ActivityWatch bucket export: {}", path.display(), e))?;
+
+    let mut windows: Vec<&Event> = Vec::new();
+    let mut afk: Vec<(DateTime<Local>, DateTime<Local>)> = Vec::new();
+    for bucket in export.buckets.values() {
+        match bucket.bucket_type.as_str() {
+            "currentwindow" => windows.extend(bucket.events.iter()),
+            "afkstatus" => afk.extend(
+                bucket
+                    .events
+                    .iter()
+                    .filter(|event| event.text("status") == Some("afk"))
+                    .map(|event| (event.start(), event.end())),
+            ),
+            _ => {}
+        }
+    }
+    if windows.is_empty() {
+        return Err(format!("{} contains no currentwindow events", path.display()));
+    }
+    windows.sort_by_key(|event| event.timestamp);
+    let afk = merge_intervals(afk);
+
+    let merge_gap = Duration::seconds(MERGE_GAP_SECONDS);
+    let mut blocks: Vec<TimeBlock> = Vec::new();
+    let mut covered_until: Option<DateTime<Local>> = None;
+    for event in windows {
+        let application = event.text("app").unwrap_or("unknown").to_string();
+        let title = event.text("title").filter(|title| !title.is_empty());
+        // Overlapping events (e.g. from two hosts) don't count twice
+        let start = match covered_until {
+            Some(until) => event.start().max(until),
+            None => event.start(),
+        };
+        let end = event.end();
+        if end <= start {
+            continue;
+        }
+        covered_until = Some(end);
+
+        for (part_start, part_end, idle) in split_idle(start, end, &afk) {
+            match blocks.last_mut() {
+                Some(last)
+                    if last.application == application
+                        && last.idle == idle
+                        && part_start.signed_duration_since(last.end_time) <= merge_gap =>
+                {
+                    last.end_time = part_end;
+                }
+                _ => {
+                    let category = crate::categorize_activity(&application, None, config);
+                    let mut block = TimeBlock::new(part_start, application.clone(), category, idle);
+                    block.end_time = part_end;
+                    blocks.push(block);
+                }
+            }
+            if let (Some(last), Some(title)) = (blocks.last_mut(), title) {
+                last.window_title = Some(title.to_string());
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+/// Sorts intervals and joins overlapping ones
+fn merge_intervals(mut intervals: Vec<(DateTime<Local>, DateTime<Local>)>) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    intervals.sort();
+    let mut merged: Vec<(DateTime<Local>, DateTime<Local>)> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Splits `start`..`end` into parts that are inside (idle) or outside the
+/// sorted, non-overlapping AFK intervals
+fn split_idle(start: DateTime<Local>, end: DateTime<Local>, afk: &[(DateTime<Local>, DateTime<Local>)]) -> Vec<(DateTime<Local>, DateTime<Local>, bool)> {
+    let mut parts = Vec::new();
+    let mut cursor = start;
+    let first = afk.partition_point(|(_, afk_end)| *afk_end <= start);
+    for (afk_start, afk_end) in afk[first..].iter().take_while(|(afk_start, _)| *afk_start < end) {
+        if *afk_start > cursor {
+            parts.push((cursor, *afk_start, false));
+        }
+        let idle_start = cursor.max(*afk_start);
+        let idle_end = end.min(*afk_end);
+        parts.push((idle_start, idle_end, true));
+        cursor = idle_end;
+    }
+    if cursor < end {
+        parts.push((cursor, end, false));
+    }
+    parts
+}
+
+/// Imports an ActivityWatch export into the block store. Days that already
+/// have blocks are left alone unless `replace` is set.
+pub fn import(config: &Config, path: &Path, replace: bool, dry_run: bool) -> Result<(), String> {
+    let blocks = read_blocks(config, path)?;
+    let mut imported = 0;
+    let mut skipped = 0;
+
+    for (date, blocks) in day_boundary::split_by_day(blocks, config.day_start) {
+        let existing = storage::load_blocks(&config.data_directory, date)?;
+        if !existing.is_empty() && !replace {
+            println!("{}: skipped, {} block(s) already recorded (use --replace)", date, existing.len());
+            skipped += 1;
+            continue;
+        }
+
+        let blocks = config.calendar.label(&blocks);
+        let total = blocks.iter().fold(Duration::zero(), |total, block| {
+            total + block.end_time.signed_duration_since(block.start_time)
+        });
+        println!("{}: {} block(s), {}", date, blocks.len(), format_duration(total));
+        imported += 1;
+
+        if !dry_run {
+            storage::save_blocks(&config.data_directory, date, &blocks)?;
+            crate::regenerate_day(config, date)?;
+        }
+    }
+
+    println!(
+        "{} {} day(s), skipped {}.",
+        if dry_run { "Would import" } else { "Imported" },
+        imported,
+        skipped
+    );
+    if dry_run {
+        println!("Dry run: nothing was written.");
+    }
+    Ok(())
+}
+
+/// Writes blocks as an ActivityWatch bucket export with a `currentwindow`
+/// bucket (one event per block) and an `afkstatus` bucket (one event per run
+/// of idle or active time), which ActivityWatch can import
+pub fn export(days: &[(NaiveDate, Vec<TimeBlock>)]) -> Result<String, String> {
+    let blocks: Vec<&TimeBlock> = days.iter().flat_map(|(_, blocks)| blocks).collect();
+    let event = |start: DateTime<Local>, end: DateTime<Local>, data: Value| Event {
+        id: None,
+        timestamp: start.with_timezone(&Utc),
+        duration: end.signed_duration_since(start).num_milliseconds() as f64 / 1000.0,
+        data: match data {
+            Value::Object(data) => data,
+            _ => Map::new(),
+        },
+    };
+
+    let windows = blocks
+        .iter()
+        .map(|block| {
+            let data = json!({
+                "app": block.application,
+                "title": block.window_title.clone().unwrap_or_default(),
+            });
+            event(block.start_time, block.end_time, data)
+        })
+        .collect();
+
+    let mut runs: Vec<(DateTime<Local>, DateTime<Local>, bool)> = Vec::new();
+    for block in &blocks {
+        match runs.last_mut() {
+            Some(last) if last.2 == block.idle && last.1 == block.start_time => last.1 = block.end_time,
+            _ => runs.push((block.start_time, block.end_time, block.idle)),
+        }
+    }
+    let afk = runs
+        .into_iter()
+        .map(|(start, end, idle)| {
+            let status = if idle { "afk" } else { "not-afk" };
+            event(start, end, json!({ "status": status }))
+        })
+        .collect();
+
+    let bucket = |watcher: &str, bucket_type: &str, events: Vec<Event>| {
+        let id = format!("{}_{}", watcher, EXPORT_HOSTNAME);
+        (
+            id.clone(),
+            Bucket {
+                id,
+                created: Some(Utc::now()),
+                name: None,
+                bucket_type: bucket_type.to_string(),
+                client: watcher.to_string(),
+                hostname: EXPORT_HOSTNAME.to_string(),
+                data: Map::new(),
+                events,
+            },
+        )
+    };
+    let export = BucketExport {
+        buckets: BTreeMap::from([
+            bucket("aw-watcher-window", "currentwindow", windows),
+            bucket("aw-watcher-afk", "afkstatus", afk),
+        ]),
+    };
+    serde_json::to_string_pretty(&export).map_err(|e| e.to_string())
+}
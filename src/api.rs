@@ -0,0 +1,441 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::config::Config;
+use crate::day_boundary;
+use crate::export;
+use crate::goals;
+use crate::storage;
+use crate::{build_daily_summary, TimeBlock};
+
+/// Most days `/blocks` answers for at once
+const MAX_BLOCK_DAYS: i64 = 31;
+
+/// The local HTTP API served by the running tracker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    /// Loopback address and port to listen on
+    pub address: String,
+    /// Listen on this Unix socket instead of `address`
+    pub unix_socket: Option<String>,
+    /// Clients send it as `Authorization: Bearer <token>`
+    pub token: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig {
+            enabled: false,
+            address: "127.0.0.1:7878".to_string(),
+            unix_socket: None,
+            token: String::new(),
+        }
+    }
+}
+
+impl ApiConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.token.trim().is_empty() {
+            return Err("api 'token' must be set when the API is enabled".to_string());
+        }
+        if self.unix_socket.is_none() {
+            let address: SocketAddr = self
+                .address
+                .parse()
+                .map_err(|_| format!("api 'address' is not an address with a port: {}", self.address))?;
+            if !address.ip().is_loopback() {
+                return Err(format!("api 'address' must be a loopback address such as 127.0.0.1, not {}", address.ip()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What the tracker shares with the API after every sample
+pub struct LiveState {
+    pub config: Config,
+    /// Logical day being tracked
+    pub date: NaiveDate,
+    /// The open block up to the last sample
+    pub current_block: Option<TimeBlock>,
+    /// Time of the last sample; `None` until the first one
+    pub updated_at: Option<DateTime<Local>>,
+    /// Recording is paused from the control socket
+    pub paused: bool,
+}
+
+pub type SharedState = Arc<Mutex<LiveState>>;
+
+/// A running API server; dropping it stops the server
+pub struct ApiServer {
+    server: Arc<Server>,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    socket: Option<PathBuf>,
+}
+
+impl ApiServer {
+    /// Starts serving `state` if the API is enabled. Failures (such as the
+    /// port being taken) are logged and leave the API off.
+    pub fn start(settings: &ApiConfig, state: &SharedState) -> Option<ApiServer> {
+        if !settings.enabled {
+            return None;
+        }
+        let (server, socket) = match bind(settings) {
+            Ok(bound) => bound,
+            Err(e) => {
+                println!("Failed to start the API: {}", e);
+                return None;
+            }
+        };
+        println!(
+            "API listening on {}",
+            socket.as_ref().map(|socket| socket.display().to_string()).unwrap_or_else(|| settings.address.clone())
+        );
+
+        let server = Arc::new(server);
+        let stopping = Arc::new(AtomicBool::new(false));
+        let thread_server = server.clone();
+        let thread_stopping = stopping.clone();
+        let thread_state = state.clone();
+        let token = settings.token.clone();
+        let thread = thread::spawn(move || loop {
+            match thread_server.recv() {
+                Ok(request) => respond(request, &token, &thread_state),
+                Err(_) if thread_stopping.load(Ordering::SeqCst) => break,
+                Err(e) => println!("API connection failed: {}", e),
+            }
+        });
+
+        Some(ApiServer {
+            server,
+            stopping,
+            thread: Some(thread),
+            socket,
+        })
+    }
+}
+
+impl Drop for ApiServer {
+    fn drop(&mut self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        if let Some(socket) = &self.socket {
+            let _ = std::fs::remove_file(socket);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn bind(settings: &ApiConfig) -> Result<(Server, Option<PathBuf>), String> {
+    let Some(socket) = &settings.unix_socket else {
+        return Server::http(&settings.address)
+            .map(|server| (server, None))
+            .map_err(|e| format!("{}: {}", settings.address, e));
+    };
+    let socket = PathBuf::from(socket);
+    let server = bind_private_socket(&socket, |bound| Server::http_unix(bound).map_err(|e| e.to_string()))?;
+    Ok((server, Some(socket)))
+}
+
+/// Binds a Unix socket at `socket` with `bind`, usable only by its owner. A
+/// socket left behind by a tracker that didn't shut down cleanly is replaced;
+/// a socket that is in use or any other file at the path is an error.
+#[cfg(unix)]
+pub fn bind_private_socket<T>(socket: &std::path::Path, bind: impl FnOnce(&std::path::Path) -> Result<T, String>) -> Result<T, String> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+    use std::os::unix::net::UnixStream;
+
+    if let Ok(metadata) = std::fs::symlink_metadata(socket) {
+        if !metadata.file_type().is_socket() {
+            return Err(format!("{} exists and is not a socket", socket.display()));
+        }
+        if UnixStream::connect(socket).is_ok() {
+            return Err(format!("{} is in use; is another tracker running?", socket.display()));
+        }
+        std::fs::remove_file(socket).map_err(|e| format!("{}: {}", socket.display(), e))?;
+    }
+
+    // Bind in a directory only we can enter and move the socket into place
+    // once it is private, so nobody can connect before the mode is set
+    let parent = socket.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+    let file_name = socket.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let private = parent.join(format!(".{}.{}.tmp", file_name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&private);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private)
+        .map_err(|e| format!("{}: {}", private.display(), e))?;
+    let bound = private.join("socket");
+    let result = bind(&bound).and_then(|bound_socket| {
+        std::fs::set_permissions(&bound, std::fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+        std::fs::rename(&bound, socket).map_err(|e| e.to_string())?;
+        Ok(bound_socket)
+    });
+    let _ = std::fs::remove_dir_all(&private);
+    result.map_err(|e| format!("{}: {}", socket.display(), e))
+}
+
+#[cfg(not(unix))]
+fn bind(settings: &ApiConfig) -> Result<(Server, Option<PathBuf>), String> {
+    if settings.unix_socket.is_some() {
+        return Err("Unix sockets are not supported on this platform".to_string());
+    }
+    Server::http(&settings.address)
+        .map(|server| (server, None))
+        .map_err(|e| format!("{}: {}", settings.address, e))
+}
+
+/// An API answer: status code and JSON body
+type Reply = (u16, Value);
+
+fn error(status: u16, message: impl Into<String>) -> Reply {
+    (status, json!({ "error": message.into() }))
+}
+
+fn respond(request: Request, token: &str, state: &SharedState) {
+    let (status, body) = handle(&request, token, state);
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").expect("valid header"));
+    if let Err(e) = request.respond(response) {
+        println!("Failed to answer API request: {}", e);
+    }
+}
+
+fn handle(request: &Request, token: &str, state: &SharedState) -> Reply {
+    if request.method() != &Method::Get {
+        return error(405, "Only GET requests are supported");
+    }
+    let authorized = request.headers().iter().any(|header| {
+        header.field.equiv("Authorization")
+            && header.value.as_str().strip_prefix("Bearer ").is_some_and(|given| same_token(given, token))
+    });
+    if !authorized {
+        return error(401, "Missing or wrong API token");
+    }
+
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let query: HashMap<&str, &str> = query.split('&').filter_map(|pair| pair.split_once('=')).collect();
+
+    // Work on a copy so the tracker isn't held up by slow requests
+    let (config, date, current_block, updated_at, paused) = {
+        let state = state.lock().unwrap();
+        (state.config.clone(), state.date, state.current_block.clone(), state.updated_at, state.paused)
+    };
+    let today = day_boundary::logical_date(Local::now(), config.day_start);
+    let result = match path.trim_end_matches('/') {
+        "/status" => status(&config, date, current_block, updated_at, paused),
+        "/blocks" => blocks(&config, &query, today),
+        "/goals" => goal_progress(&config, &query, today),
+        path => match path.strip_prefix("/summary/") {
+            Some(day) => match parse_date(day) {
+                Ok(day) => summary(&config, day),
+                Err(reply) => return reply,
+            },
+            None => return error(404, format!("No such endpoint: {}", path)),
+        },
+    };
+    result.unwrap_or_else(|e| error(500, e))
+}
+
+/// Compares every byte instead of stopping at the first difference, so the
+/// answer time doesn't tell how much of a guessed token was right
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given.bytes().zip(token.bytes()).fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, Reply> {
+    text.parse()
+        .map_err(|_| error(400, format!("Invalid date (expected YYYY-MM-DD): {}", text)))
+}
+
+/// A date query parameter, or `default` without one
+fn date_parameter(query: &HashMap<&str, &str>, name: &str, default: NaiveDate) -> Result<NaiveDate, Reply> {
+    query.get(name).map_or(Ok(default), |text| parse_date(text))
+}
+
+fn seconds_map(durations: &HashMap<String, Duration>) -> Value {
+    let map: Map<String, Value> = durations
+        .iter()
+        .map(|(key, duration)| (key.clone(), json!(duration.num_seconds())))
+        .collect();
+    Value::Object(map)
+}
+
+/// The open block and today's totals
+fn status(
+    config: &Config,
+    date: NaiveDate,
+    current_block: Option<TimeBlock>,
+    updated_at: Option<DateTime<Local>>,
+    paused: bool,
+) -> Result<Reply, String> {
+    let blocks = storage::load_effective_blocks(&config.data_directory, date)?;
+    let today = if blocks.is_empty() {
+        Value::Null
+    } else {
+        export::day_objects(config, &[(date, blocks)]).pop().unwrap_or(Value::Null)
+    };
+    let idle = current_block.as_ref().is_some_and(|block| block.idle);
+    let current_block = current_block
+        .map(|block| export::block_objects(&[(date, vec![block])]).pop().unwrap_or(Value::Null))
+        .unwrap_or(Value::Null);
+
+    Ok((
+        200,
+        json!({
+            "date": date.to_string(),
+            "updated_at": updated_at.map(|time| time.to_rfc3339()),
+            "idle": idle,
+            "paused": paused,
+            "current_block": current_block,
+            "today": today,
+        }),
+    ))
+}
+
+/// Blocks of `from`..`to` (both default to today, at most `MAX_BLOCK_DAYS`
+/// days), with corrections applied
+fn blocks(config: &Config, query: &HashMap<&str, &str>, today: NaiveDate) -> Result<Reply, String> {
+    let from = match date_parameter(query, "from", today) {
+        Ok(from) => from,
+        Err(reply) => return Ok(reply),
+    };
+    let to = match date_parameter(query, "to", today) {
+        Ok(to) => to,
+        Err(reply) => return Ok(reply),
+    };
+    if from > to {
+        return Ok(error(400, format!("'from' ({}) is after 'to' ({})", from, to)));
+    }
+    if (to - from).num_days() >= MAX_BLOCK_DAYS {
+        return Ok(error(400, format!("At most {} days of blocks can be asked for at once", MAX_BLOCK_DAYS)));
+    }
+    let days = export::load_days(config, from, to)?;
+    Ok((200, Value::Array(export::block_objects(&days))))
+}
+
+/// The day's totals with the time per category, application and project
+fn summary(config: &Config, date: NaiveDate) -> Result<Reply, String> {
+    let blocks = storage::load_effective_blocks(&config.data_directory, date)?;
+    if blocks.is_empty() {
+        return Ok(error(404, format!("Nothing recorded on {}", date)));
+    }
+    let summary = build_daily_summary(&blocks, date, config.focus_tolerance());
+    let mut object = export::day_objects(config, &[(date, blocks)]).pop().unwrap_or(Value::Null);
+    if let Value::Object(object) = &mut object {
+        object.insert("categories".to_string(), seconds_map(&summary.category_breakdown));
+        object.insert("applications".to_string(), seconds_map(&summary.application_breakdown));
+        object.insert("projects".to_string(), seconds_map(&summary.project_breakdown));
+    }
+    Ok((200, object))
+}
+
+/// Progress on the goals of `date` (default today)
+fn goal_progress(config: &Config, query: &HashMap<&str, &str>, today: NaiveDate) -> Result<Reply, String> {
+    let date = match date_parameter(query, "date", today) {
+        Ok(date) => date,
+        Err(reply) => return Ok(reply),
+    };
+    let blocks = storage::load_effective_blocks(&config.data_directory, date)?;
+    let progress = goals::progress(config, date, &blocks, Local::now())?;
+    let goals: Vec<Value> = progress
+        .iter()
+        .map(|item| {
+            json!({
+                "name": item.goal.name,
+                "description": item.goal.describe(),
+                "state": item.state.label(),
+                "actual_seconds": item.actual.num_seconds(),
+                "percent": item.percent(),
+                "amount": item.amount(),
+                "streak": item.streak,
+            })
+        })
+        .collect();
+    Ok((200, json!({ "date": date.to_string(), "goals": goals })))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    fn socket_settings(name: &str) -> (ApiConfig, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("timesense-api-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("api.sock");
+        let settings = ApiConfig {
+            enabled: true,
+            unix_socket: Some(socket.to_string_lossy().into_owned()),
+            token: "secret".to_string(),
+            ..ApiConfig::default()
+        };
+        (settings, socket)
+    }
+
+    #[test]
+    fn tokens_must_match_exactly() {
+        assert!(same_token("secret", "secret"));
+        assert!(!same_token("secreT", "secret"));
+        assert!(!same_token("secret2", "secret"));
+        assert!(!same_token("", "secret"));
+    }
+
+    #[test]
+    fn block_ranges_are_capped() {
+        let config = Config::default();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let query = HashMap::from([("from", "2026-01-01"), ("to", "2026-10-19")]);
+        let (status, body) = blocks(&config, &query, today).unwrap();
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().contains("31 days"));
+    }
+
+    #[test]
+    fn stale_sockets_are_replaced_but_live_ones_are_kept() {
+        let (settings, socket) = socket_settings("stale");
+        drop(UnixListener::bind(&socket).unwrap());
+        let (server, _) = bind(&settings).unwrap();
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(UnixStream::connect(&socket).is_ok());
+        // Only the socket is left in the directory
+        assert_eq!(std::fs::read_dir(socket.parent().unwrap()).unwrap().count(), 1);
+
+        let error = bind(&settings).err().unwrap();
+        assert!(error.contains("in use"), "{}", error);
+        drop(server);
+        std::fs::remove_dir_all(socket.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn other_files_at_the_socket_path_are_left_alone() {
+        let (settings, socket) = socket_settings("file");
+        std::fs::write(&socket, "notes").unwrap();
+
+        let error = bind(&settings).err().unwrap();
+        assert!(error.contains("not a socket"), "{}", error);
+        assert_eq!(std::fs::read_to_string(&socket).unwrap(), "notes");
+        std::fs::remove_dir_all(socket.parent().unwrap()).unwrap();
+    }
+}
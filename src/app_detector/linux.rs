@@ -6,32 +6,44 @@ use std::process::Command;
 
 pub struct LinuxAppDetector {
     active_app: Arc<Mutex<String>>,
+    active_title: Arc<Mutex<String>>,
+    active_pid: Arc<Mutex<Option<u32>>>,
     stop_signal: Arc<Mutex<bool>>,
 }
 
 impl LinuxAppDetector {
     pub fn new() -> Self {
         let active_app = Arc::new(Mutex::new(String::new()));
+        let active_title = Arc::new(Mutex::new(String::new()));
+        let active_pid = Arc::new(Mutex::new(None));
         let stop_signal = Arc::new(Mutex::new(false));
         
         let detector = LinuxAppDetector {
             active_app: active_app.clone(),
+            active_title: active_title.clone(),
+            active_pid: active_pid.clone(),
             stop_signal: stop_signal.clone(),
         };
         
         let thread_active_app = active_app.clone();
+        let thread_active_title = active_title.clone();
+        let thread_active_pid = active_pid.clone();
         let thread_stop_signal = stop_signal.clone();
         
         std::thread::spawn(move || {
             while !*thread_stop_signal.lock().unwrap() {
-                // Try to get the active window using xdotool
+                // Try to get the active window using xdotool; without one
+                // there is no active process
+                let mut active_pid = None;
                 if let Ok(output) = Command::new("xdotool")
-                    .args(&["getactivewindow", "getwindowpid"])
+                    .args(["getactivewindow", "getwindowpid"])
                     .output() {
                     
                     if output.status.success() {
                         if let Ok(pid_str) = String::from_utf8(output.stdout) {
                             if let Ok(pid) = pid_str.trim().parse::<u32>() {
+                                active_pid = Some(pid);
+
                                 // Get the process name from the PID
                                 if let Ok(output) = Command::new("ps")
                                     .args(&["-p", &pid.to_string(), "-o", "comm="])
@@ -48,6 +60,19 @@ impl LinuxAppDetector {
                         }
                     }
                 }
+                *thread_active_pid.lock().unwrap() = active_pid;
+
+                // Get the title of the active window
+                if let Ok(output) = Command::new("xdotool")
+                    .args(["getactivewindow", "getwindowname"])
+                    .output() {
+
+                    if output.status.success() {
+                        if let Ok(title) = String::from_utf8(output.stdout) {
+                            *thread_active_title.lock().unwrap() = title.trim().to_string();
+                        }
+                    }
+                }
                 
                 thread::sleep(Duration::from_millis(500));
             }
@@ -65,6 +90,15 @@ impl AppDetector for LinuxAppDetector {
     fn get_active_application(&self) -> String {
         self.active_app.lock().unwrap().clone()
     }
+
+    fn get_active_window_title(&self) -> Option<String> {
+        let title = self.active_title.lock().unwrap().clone();
+        if title.is_empty() { None } else { Some(title) }
+    }
+
+    fn get_active_pid(&self) -> Option<u32> {
+        *self.active_pid.lock().unwrap()
+    }
 }
 
 impl Drop for LinuxAppDetector {
@@ -6,20 +6,24 @@ use std::process::Command;
 
 pub struct MacOSAppDetector {
     active_app: Arc<Mutex<String>>,
+    active_title: Arc<Mutex<String>>,
     stop_signal: Arc<Mutex<bool>>,
 }
 
 impl MacOSAppDetector {
     pub fn new() -> Self {
         let active_app = Arc::new(Mutex::new(String::new()));
+        let active_title = Arc::new(Mutex::new(String::new()));
         let stop_signal = Arc::new(Mutex::new(false));
         
         let detector = MacOSAppDetector {
             active_app: active_app.clone(),
+            active_title: active_title.clone(),
             stop_signal: stop_signal.clone(),
         };
         
         let thread_active_app = active_app.clone();
+        let thread_active_title = active_title.clone();
         let thread_stop_signal = stop_signal.clone();
         
         std::thread::spawn(move || {
@@ -36,6 +40,19 @@ impl MacOSAppDetector {
                         }
                     }
                 }
+
+                // Get the title of the frontmost window (empty if the app has none)
+                if let Ok(output) = Command::new("osascript")
+                    .args(["-e", "tell application \"System Events\" to get name of front window of (first application process whose frontmost is true)"])
+                    .output() {
+
+                    let title = if output.status.success() {
+                        String::from_utf8(output.stdout).unwrap_or_default()
+                    } else {
+                        String::new()
+                    };
+                    *thread_active_title.lock().unwrap() = title.trim().to_string();
+                }
                 
                 thread::sleep(Duration::from_millis(500));
             }
@@ -53,6 +70,11 @@ impl AppDetector for MacOSAppDetector {
     fn get_active_application(&self) -> String {
         self.active_app.lock().unwrap().clone()
     }
+
+    fn get_active_window_title(&self) -> Option<String> {
+        let title = self.active_title.lock().unwrap().clone();
+        if title.is_empty() { None } else { Some(title) }
+    }
 }
 
 impl Drop for MacOSAppDetector {
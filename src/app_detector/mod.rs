@@ -1,5 +1,15 @@
 pub trait AppDetector {
     fn get_active_application(&self) -> String;
+
+    /// Title of the focused window, if the platform exposes it
+    fn get_active_window_title(&self) -> Option<String> {
+        None
+    }
+
+    /// Process ID of the focused window, if the platform exposes it
+    fn get_active_pid(&self) -> Option<u32> {
+        None
+    }
 }
 
 #[cfg(target_os = "macos")]
@@ -128,6 +128,11 @@ impl AppDetector for WindowsAppDetector {
     fn get_active_application(&self) -> String {
         self.active_process.lock().unwrap().clone()
     }
+
+    fn get_active_window_title(&self) -> Option<String> {
+        let title = self.active_window.lock().unwrap().clone();
+        if title.is_empty() { None } else { Some(title) }
+    }
 }
 
 impl Drop for WindowsAppDetector {
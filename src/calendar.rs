@@ -0,0 +1,281 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::iter;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::day_boundary;
+use crate::ics::{self, CalendarEvent, Event};
+use crate::TimeBlock;
+
+/// A calendar file as last read, with its modification time then
+struct CachedCalendar {
+    modified: SystemTime,
+    events: Vec<CalendarEvent>,
+}
+
+/// Parsed calendar files by path, read again only once they change
+static CALENDARS: Mutex<BTreeMap<String, CachedCalendar>> = Mutex::new(BTreeMap::new());
+
+/// Forgets the parsed calendar files, so they're read again at next use
+pub fn clear_cache() {
+    CALENDARS.lock().unwrap().clear();
+}
+
+/// Calendars whose meetings label the recorded time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Local iCalendar (.ics) files, e.g. exported or synced calendars
+    pub files: Vec<String>,
+    /// Category of active time during a meeting
+    pub category: String,
+    /// Applications counted as time in calls (case-insensitive, matched
+    /// anywhere in the application name)
+    pub call_apps: Vec<String>,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        CalendarConfig {
+            files: Vec::new(),
+            category: "meeting".to_string(),
+            call_apps: ["zoom", "teams", "meet", "skype", "webex", "facetime", "jitsi"]
+                .iter()
+                .map(|app| app.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// A scheduled meeting of one day and the time spent in call apps during it
+#[derive(Debug, Clone)]
+pub struct Meeting {
+    pub title: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub call_time: Duration,
+}
+
+/// Scheduled meetings of a day compared with the time spent in call apps
+#[derive(Debug, Clone, Default)]
+pub struct MeetingSummary {
+    pub meetings: Vec<Meeting>,
+    /// Total length of the meetings; overlapping meetings count once
+    pub scheduled: Duration,
+    /// Active time in call apps over the whole day
+    pub call_time: Duration,
+    /// The part of `call_time` outside any meeting
+    pub unscheduled_call_time: Duration,
+}
+
+impl CalendarConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.category.trim().is_empty() {
+            return Err("calendar 'category' must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    fn is_call_app(&self, application: &str) -> bool {
+        let application = application.to_lowercase();
+        self.call_apps
+            .iter()
+            .any(|app| application.contains(&app.to_lowercase()))
+    }
+
+    /// Timed events of every calendar that overlap `from`..`to`, by start
+    /// time. A file is only parsed again once its modification time changes.
+    /// Files and events that can't be read are reported and skipped.
+    pub fn events(&self, from: DateTime<Local>, to: DateTime<Local>) -> Vec<Event> {
+        let mut calendars = CALENDARS.lock().unwrap();
+        let mut events = Vec::new();
+        for file in &self.files {
+            let modified = match fs::metadata(file).and_then(|metadata| metadata.modified()) {
+                Ok(modified) => modified,
+                Err(e) => {
+                    println!("Failed to read calendar {}: {}", file, e);
+                    calendars.remove(file);
+                    continue;
+                }
+            };
+            let current = matches!(calendars.get(file), Some(cached) if cached.modified == modified);
+            if !current {
+                let text = match fs::read_to_string(file) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        println!("Failed to read calendar {}: {}", file, e);
+                        calendars.remove(file);
+                        continue;
+                    }
+                };
+                let (parsed, warnings) = ics::parse_calendar(&text);
+                for warning in warnings {
+                    println!("Calendar {}: {}", file, warning);
+                }
+                calendars.insert(file.clone(), CachedCalendar { modified, events: parsed });
+            }
+            events.extend(calendars[file].events.iter().flat_map(|event| event.occurrences(from, to)));
+        }
+        events.sort_by_key(|event| event.start);
+        events
+    }
+
+    /// Splits active blocks at meeting boundaries and moves the parts during
+    /// a meeting to the meeting category, with the meeting's title. Idle
+    /// blocks are kept as they are.
+    pub fn label(&self, blocks: &[TimeBlock]) -> Vec<TimeBlock> {
+        let from = blocks.iter().map(|block| block.start_time).min();
+        let to = blocks.iter().map(|block| block.end_time).max();
+        let events = match (from, to) {
+            (Some(from), Some(to)) if !self.files.is_empty() => self.events(from, to),
+            _ => return blocks.to_vec(),
+        };
+
+        let mut labeled = Vec::new();
+        for block in blocks {
+            if block.idle {
+                labeled.push(block.clone());
+                continue;
+            }
+            let mut cuts: Vec<DateTime<Local>> = events
+                .iter()
+                .flat_map(|event| [event.start, event.end])
+                .filter(|time| block.start_time < *time && *time < block.end_time)
+                .collect();
+            cuts.sort();
+            cuts.dedup();
+
+            let mut start = block.start_time;
+            for end in cuts.into_iter().chain(iter::once(block.end_time)) {
+                let mut part = block.clone();
+                part.start_time = start;
+                part.end_time = end;
+                if let Some(event) = events.iter().find(|event| event.start <= start && start < event.end) {
+                    part.activity_type = self.category.clone();
+                    part.meeting = Some(event.summary.clone());
+                }
+                labeled.push(part);
+                start = end;
+            }
+        }
+        labeled
+    }
+
+    /// The meetings of the logical day `date` and the time spent in call apps
+    pub fn meetings(&self, date: NaiveDate, day_start: NaiveTime, blocks: &[TimeBlock]) -> MeetingSummary {
+        if self.files.is_empty() {
+            return MeetingSummary::default();
+        }
+        let day_end = day_boundary::start_of_day(date + Duration::days(1), day_start);
+        let events = self.events(day_boundary::start_of_day(date, day_start), day_end);
+        let calls: Vec<&TimeBlock> = blocks
+            .iter()
+            .filter(|block| !block.idle && self.is_call_app(&block.application))
+            .collect();
+        let call_time_between = |start: DateTime<Local>, end: DateTime<Local>| {
+            calls.iter().fold(Duration::zero(), |total, block| {
+                let overlap = block.end_time.min(end).signed_duration_since(block.start_time.max(start));
+                total + overlap.max(Duration::zero())
+            })
+        };
+
+        let meetings: Vec<Meeting> = events
+            .iter()
+            .map(|event| Meeting {
+                title: event.summary.clone(),
+                start: event.start,
+                end: event.end,
+                call_time: call_time_between(event.start, event.end),
+            })
+            .collect();
+
+        // Merge overlapping meetings so double-booked time counts once
+        let mut scheduled = Duration::zero();
+        let mut during_meetings = Duration::zero();
+        let mut current: Option<(DateTime<Local>, DateTime<Local>)> = None;
+        for event in &events {
+            match current {
+                Some((start, end)) if event.start <= end => current = Some((start, end.max(event.end))),
+                _ => {
+                    if let Some((start, end)) = current {
+                        scheduled += end.signed_duration_since(start);
+                        during_meetings += call_time_between(start, end);
+                    }
+                    current = Some((event.start, event.end));
+                }
+            }
+        }
+        if let Some((start, end)) = current {
+            scheduled += end.signed_duration_since(start);
+            during_meetings += call_time_between(start, end);
+        }
+
+        let call_time = calls.iter().fold(Duration::zero(), |total, block| {
+            total + block.end_time.signed_duration_since(block.start_time)
+        });
+        MeetingSummary {
+            meetings,
+            scheduled,
+            call_time,
+            unscheduled_call_time: call_time - during_meetings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::fs::File;
+    use std::time::Duration as StdDuration;
+
+    fn event(summary: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:{}\r\nDTSTART:20261019T100000\r\nDTEND:20261019T110000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+            summary
+        )
+    }
+
+    fn summaries(config: &CalendarConfig) -> Vec<String> {
+        let from = Local.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap();
+        let to = from + Duration::days(1);
+        config.events(from, to).into_iter().map(|event| event.summary).collect()
+    }
+
+    #[test]
+    fn files_are_read_again_only_once_they_change() {
+        let path = std::env::temp_dir().join(format!("timesense-calendar-{}.ics", std::process::id()));
+        let config = CalendarConfig {
+            files: vec![path.to_string_lossy().into_owned()],
+            ..CalendarConfig::default()
+        };
+        let set_modified = |time: SystemTime| File::options().write(true).open(&path).unwrap().set_modified(time).unwrap();
+        let earlier = SystemTime::now() - StdDuration::from_secs(3600);
+
+        fs::write(&path, event("Planning")).unwrap();
+        set_modified(earlier);
+        assert_eq!(summaries(&config), vec!["Planning"]);
+
+        // Same modification time: the parsed calendar is used
+        fs::write(&path, event("Review")).unwrap();
+        set_modified(earlier);
+        assert_eq!(summaries(&config), vec!["Planning"]);
+
+        let later = SystemTime::now();
+        set_modified(later);
+        assert_eq!(summaries(&config), vec!["Review"]);
+
+        // A configuration reload clears the cache
+        fs::write(&path, event("Retro")).unwrap();
+        set_modified(later);
+        assert_eq!(summaries(&config), vec!["Review"]);
+        clear_cache();
+        assert_eq!(summaries(&config), vec!["Retro"]);
+
+        fs::remove_file(&path).unwrap();
+        assert!(summaries(&config).is_empty());
+    }
+}
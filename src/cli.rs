@@ -0,0 +1,278 @@
+use chrono::{Duration, NaiveDate, NaiveTime};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::control::parse_length;
+
+#[derive(Debug, Parser)]
+#[command(name = "timesense", version, about = "Automated time awareness tool")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the tracker in the foreground (the default when no command is given)
+    Track,
+
+    /// Re-apply the current categorization rules to stored days and
+    /// regenerate their summaries and reports
+    Recategorize {
+        /// First day to process (YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        /// Last day to process (YYYY-MM-DD); defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Only report how much time would move between categories
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Generate a multi-day trend report (HTML, Markdown and heatmap JSON)
+    /// for a week, a month or any range of days
+    #[command(group(ArgGroup::new("period").required(true).args(["week", "month", "from"])))]
+    Report {
+        /// ISO week, e.g. 2026-W42
+        #[arg(long)]
+        week: Option<String>,
+        /// Month, e.g. 2026-10
+        #[arg(long)]
+        month: Option<String>,
+        /// First day of a custom range (YYYY-MM-DD)
+        #[arg(long, requires = "to")]
+        from: Option<NaiveDate>,
+        /// Last day of a custom range (YYYY-MM-DD)
+        #[arg(long, requires = "from")]
+        to: Option<NaiveDate>,
+        /// Category the heatmap shows the share of (default: productive)
+        #[arg(long)]
+        heatmap_category: Option<String>,
+    },
+
+    /// List and correct the recorded blocks of a day
+    Blocks {
+        /// Day to work on (YYYY-MM-DD); defaults to today
+        #[arg(long, global = true)]
+        date: Option<NaiveDate>,
+        #[command(subcommand)]
+        action: BlocksAction,
+    },
+
+    /// Show progress on the configured goals
+    Goals {
+        /// Day to check (YYYY-MM-DD); defaults to today
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+
+    /// Send a test desktop notification
+    NotifyTest,
+
+    /// Export recorded blocks, or totals per app, category or day
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// First day to export (YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        /// Last day to export (YYYY-MM-DD); defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        #[arg(long, value_enum, default_value_t = Granularity::Block)]
+        granularity: Granularity,
+        /// With --format ics: leave out sessions shorter than this many minutes
+        #[arg(long, default_value_t = 5)]
+        min_session: u64,
+        /// With --format ics: only these categories (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        categories: Vec<String>,
+        /// File to write to instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Import history exported from another time tracker into the block store
+    Import {
+        #[arg(long, value_enum)]
+        format: ImportFormat,
+        /// File exported from the other tool
+        file: PathBuf,
+        /// Replace days that already have recorded blocks instead of skipping
+        /// them (ActivityWatch only)
+        #[arg(long)]
+        replace: bool,
+        /// Only report which days would be imported
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Roll project time up into timesheet entries and write them as CSV for
+    /// importing into a time-tracking tool
+    Timesheet {
+        #[arg(long, value_enum)]
+        tool: TimesheetTool,
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        /// Last day to include (YYYY-MM-DD); defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Round entries to a multiple of this many minutes (0 to disable)
+        #[arg(long)]
+        round_minutes: Option<u64>,
+        /// Drop entries shorter than this many minutes
+        #[arg(long)]
+        min_minutes: Option<u64>,
+        /// Merge entries of the same project separated by at most this many minutes
+        #[arg(long)]
+        merge_gap_minutes: Option<u64>,
+        /// File to write to instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Show the tracked time behind each git commit and each merged branch
+    Commits {
+        /// Repositories to read; defaults to those in the `projects` search roots
+        repos: Vec<PathBuf>,
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: NaiveDate,
+        /// Last day to include (YYYY-MM-DD); defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Only commits whose author matches this pattern (as in `git log
+        /// --author`); defaults to the repository's `user.email`
+        #[arg(long)]
+        author: Option<String>,
+    },
+
+    /// Pause the running tracker, for a while or until `timesense resume`
+    Pause {
+        /// How long to pause, e.g. 30m or 1h30m (at most 24h)
+        #[arg(long = "for", value_parser = parse_length)]
+        length: Option<Duration>,
+    },
+
+    /// Resume the paused tracker
+    Resume,
+
+    /// Tag every block the running tracker records from now on
+    #[command(group(ArgGroup::new("tag").required(true).args(["label", "clear"])))]
+    Tag {
+        label: Option<String>,
+        /// Stop tagging blocks
+        #[arg(long)]
+        clear: bool,
+    },
+
+    /// Start a focus session: its blocks are tagged "focus", switching to a
+    /// distraction notifies you and the end reports how it went
+    StartFocus {
+        /// Length of the session, e.g. 25m or 1h (at most 24h)
+        #[arg(long = "for", default_value = "25m", value_parser = parse_length)]
+        length: Duration,
+    },
+
+    /// Stop the running tracker and generate today's report, as Ctrl+C does
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// A Markdown table with readable durations
+    Markdown,
+    /// Calendar events, one per activity session
+    Ics,
+    /// An ActivityWatch bucket export (window and AFK events)
+    #[value(name = "activitywatch")]
+    ActivityWatch,
+    /// Timewarrior data file lines
+    Timewarrior,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ImportFormat {
+    /// An ActivityWatch bucket export (JSON)
+    #[value(name = "activitywatch")]
+    ActivityWatch,
+    /// A Timewarrior data file, imported as manual blocks
+    Timewarrior,
+}
+
+/// What one exported row stands for
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Granularity {
+    /// Every recorded block
+    Block,
+    /// Total time per application
+    App,
+    /// Total time per category, with idle time as its own category
+    Category,
+    /// Summary of each tracked day
+    Day,
+}
+
+/// Block numbers are the 1-based positions shown by `timesense blocks list`
+#[derive(Debug, Subcommand)]
+pub enum BlocksAction {
+    /// Show the day's blocks with corrections applied
+    List,
+    /// Change the category and/or project of a block
+    Reassign {
+        index: usize,
+        #[arg(long)]
+        category: Option<String>,
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Split a block in two at a time of day (HH:MM or HH:MM:SS)
+    Split {
+        index: usize,
+        #[arg(long)]
+        at: NaiveTime,
+    },
+    /// Merge a run of consecutive blocks into one
+    Merge { first: usize, last: usize },
+    /// Attach a note to a block
+    Note { index: usize, text: String },
+    /// Add tags to a block
+    Tag {
+        index: usize,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Record time spent away from the computer, e.g. a whiteboard meeting
+    Add {
+        #[arg(long)]
+        start: NaiveTime,
+        #[arg(long)]
+        end: NaiveTime,
+        #[arg(long)]
+        label: String,
+        #[arg(long, default_value = "meeting")]
+        category: String,
+        #[arg(long)]
+        project: Option<String>,
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Remove the most recent correction
+    Undo,
+}
+
+/// Time-tracking tool whose CSV import format a timesheet is written in
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum TimesheetTool {
+    Toggl,
+    Clockify,
+    Harvest,
+}
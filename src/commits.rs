@@ -0,0 +1,333 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::day_boundary;
+use crate::export;
+use crate::time_format::format_duration;
+use crate::TimeBlock;
+
+/// `git log` format of a commit: full and short hash, commit time and
+/// subject, separated by the unit separator character
+const COMMIT_FORMAT: &str = "--format=%H%x1f%h%x1f%ct%x1f%s";
+
+/// A commit and the tracked time in its repository since the commit before it
+struct Commit {
+    hash: String,
+    short_hash: String,
+    time: DateTime<Local>,
+    subject: String,
+    tracked: Duration,
+}
+
+/// A branch merged into the checked-out branch and the tracked time of the
+/// commits it brought in
+struct MergedBranch {
+    name: String,
+    merged_at: DateTime<Local>,
+    commits: usize,
+    tracked: Duration,
+}
+
+/// Runs git in `repo` and returns its standard output
+fn git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed in {}: {}",
+            args[0],
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn parse_timestamp(seconds: &str) -> Option<DateTime<Local>> {
+    Local.timestamp_opt(seconds.parse().ok()?, 0).single()
+}
+
+/// Parses a line written with `COMMIT_FORMAT`
+fn parse_commit(line: &str) -> Option<Commit> {
+    let mut fields = line.splitn(4, '\u{1f}');
+    Some(Commit {
+        hash: fields.next()?.to_string(),
+        short_hash: fields.next()?.to_string(),
+        time: parse_timestamp(fields.next()?)?,
+        subject: fields.next().unwrap_or_default().to_string(),
+        tracked: Duration::zero(),
+    })
+}
+
+/// The branch name in a merge commit subject: "Merge branch 'x'", "Merge
+/// remote-tracking branch 'origin/x'" or "Merge pull request #1 from owner/x"
+fn branch_of_merge(subject: &str) -> Option<String> {
+    if let Some(rest) = subject.strip_prefix("Merge pull request ") {
+        let source = rest.split(" from ").nth(1)?.split_whitespace().next()?;
+        let branch = source.split_once('/').map(|(_, branch)| branch).unwrap_or(source);
+        return Some(branch.to_string());
+    }
+    let quoted = subject.strip_prefix("Merge ")?.split('\'').nth(1)?;
+    Some(quoted.strip_prefix("origin/").unwrap_or(quoted).to_string())
+}
+
+/// Active time of `blocks` between `start` and `end`
+fn tracked_between(blocks: &[&TimeBlock], start: DateTime<Local>, end: DateTime<Local>) -> Duration {
+    blocks.iter().fold(Duration::zero(), |total, block| {
+        let overlap = block.end_time.min(end).signed_duration_since(block.start_time.max(start));
+        total + overlap.max(Duration::zero())
+    })
+}
+
+/// Commits between `start` and `end` on any branch, narrowed by the `git
+/// log` arguments in `author_filter`, oldest first. Each gets the time of
+/// `blocks` since the commit before it (which may be older than `start`),
+/// but never from before `start`.
+fn commits(
+    repo: &Path,
+    author_filter: &[String],
+    blocks: &[&TimeBlock],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<Vec<Commit>, String> {
+    let log = |range: &[&str]| {
+        let mut args = vec!["log", "--all", COMMIT_FORMAT];
+        args.extend(author_filter.iter().map(String::as_str));
+        args.extend(range);
+        git(repo, &args)
+    };
+
+    let before = format!("--before={}", start.to_rfc3339());
+    let mut previous = log(&["-1", &before])?
+        .lines()
+        .find_map(parse_commit)
+        .map(|commit| commit.time.max(start))
+        .unwrap_or(start);
+
+    let since = format!("--since={}", start.to_rfc3339());
+    let until = format!("--until={}", end.to_rfc3339());
+    let mut commits: Vec<Commit> = log(&[&since, &until])?.lines().filter_map(parse_commit).collect();
+    commits.sort_by_key(|commit| commit.time);
+
+    for commit in &mut commits {
+        commit.tracked = tracked_between(blocks, previous, commit.time);
+        previous = previous.max(commit.time);
+    }
+    Ok(commits)
+}
+
+/// Branches merged into the checked-out branch between `start` and `end`,
+/// with the tracked time of their commits
+fn merged_branches(
+    repo: &Path,
+    commits: &[Commit],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<Vec<MergedBranch>, String> {
+    let tracked: HashMap<&str, Duration> = commits
+        .iter()
+        .map(|commit| (commit.hash.as_str(), commit.tracked))
+        .collect();
+    let since = format!("--since={}", start.to_rfc3339());
+    let until = format!("--until={}", end.to_rfc3339());
+    let merges = git(
+        repo,
+        &["log", "--merges", "--first-parent", "--format=%ct%x1f%P%x1f%s", &since, &until],
+    )?;
+
+    let mut branches = Vec::new();
+    for line in merges.lines() {
+        let fields: Vec<&str> = line.splitn(3, '\u{1f}').collect();
+        let (Some(merged_at), Some(parents), Some(subject)) = (
+            fields.first().and_then(|time| parse_timestamp(time)),
+            fields.get(1),
+            fields.get(2),
+        ) else {
+            continue;
+        };
+        let parents: Vec<&str> = parents.split_whitespace().collect();
+        let [mainline, tip, ..] = parents[..] else {
+            continue;
+        };
+
+        // The commits the merge brought in
+        let brought_in = git(repo, &["rev-list", &format!("{}..{}", mainline, tip)])?;
+        let hashes: Vec<&str> = brought_in.lines().collect();
+        branches.push(MergedBranch {
+            name: branch_of_merge(subject).unwrap_or_else(|| tip.chars().take(7).collect()),
+            merged_at,
+            commits: hashes.len(),
+            tracked: hashes
+                .iter()
+                .filter_map(|hash| tracked.get(hash))
+                .fold(Duration::zero(), |total, duration| total + *duration),
+        });
+    }
+    branches.sort_by_key(|branch| branch.merged_at);
+    Ok(branches)
+}
+
+/// The author whose commits count and the `git log` arguments selecting
+/// them: `author` as a pattern if given, else the repository's `user.email`.
+/// The address is matched as it is, so characters such as `.` and `+` in it
+/// aren't read as a regex.
+fn author_filter(repo: &Path, author: Option<&str>) -> (Option<String>, Vec<String>) {
+    if let Some(author) = author {
+        return (Some(author.to_string()), vec![format!("--author={}", author)]);
+    }
+    let email = git(repo, &["config", "user.email"])
+        .ok()
+        .map(|email| email.trim().to_string())
+        .filter(|email| !email.is_empty());
+    match email {
+        Some(email) => {
+            let filter = vec![format!("--author={}", email), "--fixed-strings".to_string()];
+            (Some(email), filter)
+        }
+        None => (None, Vec::new()),
+    }
+}
+
+/// Prints the commits of one repository with the time behind each, the time
+/// since the last commit and the merged branches
+fn report_repository(
+    repo: &Path,
+    author: Option<&str>,
+    blocks: &[&TimeBlock],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<(), String> {
+    let repo = repo
+        .canonicalize()
+        .map_err(|e| format!("Failed to open {}: {}", repo.display(), e))?;
+    let name = repo
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("{} is not a repository", repo.display()))?;
+    let (author, author_filter) = author_filter(&repo, author);
+
+    // Time in the repository, as attributed by the project resolver or a
+    // correction
+    let blocks: Vec<&TimeBlock> = blocks
+        .iter()
+        .copied()
+        .filter(|block| block.project.as_deref() == Some(name.as_str()))
+        .collect();
+    let commits = commits(&repo, &author_filter, &blocks, start, end)?;
+    let merged = merged_branches(&repo, &commits, start, end)?;
+
+    println!(
+        "{} ({}): {} commit(s) by {}",
+        name,
+        repo.display(),
+        commits.len(),
+        author.as_deref().unwrap_or("any author")
+    );
+    for commit in &commits {
+        println!(
+            "  {}  {}  {:>8}  {}",
+            commit.time.format("%Y-%m-%d %H:%M"),
+            commit.short_hash,
+            format_duration(commit.tracked),
+            commit.subject
+        );
+    }
+    let last_commit = commits.last().map(|commit| commit.time.max(start)).unwrap_or(start);
+    println!("  After the last commit: {}", format_duration(tracked_between(&blocks, last_commit, end)));
+
+    if !merged.is_empty() {
+        println!("  Merged branches:");
+        for branch in &merged {
+            println!(
+                "    {}: {} in {} commit(s), merged {}",
+                branch.name,
+                format_duration(branch.tracked),
+                branch.commits,
+                branch.merged_at.format("%Y-%m-%d %H:%M")
+            );
+        }
+    }
+    println!("  Tracked in {}: {}", name, format_duration(tracked_between(&blocks, start, end)));
+    Ok(())
+}
+
+/// Correlates the tracked time of each repository with its commit history.
+/// Without `repos`, the repositories in the `projects` search roots are used;
+/// without `author`, each repository's `user.email`.
+pub fn run(
+    config: &Config,
+    repos: &[PathBuf],
+    from: NaiveDate,
+    to: NaiveDate,
+    author: Option<&str>,
+) -> Result<(), String> {
+    let repos = if repos.is_empty() {
+        config.projects.repositories()
+    } else {
+        repos.to_vec()
+    };
+    if repos.is_empty() {
+        return Err("No repositories given and none found in the 'projects' search roots".to_string());
+    }
+
+    let days = export::load_days(config, from, to)?;
+    let blocks: Vec<&TimeBlock> = days
+        .iter()
+        .flat_map(|(_, blocks)| blocks)
+        .filter(|block| !block.idle)
+        .collect();
+    let start = day_boundary::start_of_day(from, config.day_start);
+    let end = day_boundary::start_of_day(to + Duration::days(1), config.day_start);
+
+    for (index, repo) in repos.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        if let Err(e) = report_repository(repo, author, &blocks, start, end) {
+            println!("{}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_addresses_are_not_patterns() {
+        let repo = std::env::temp_dir().join(format!("timesense-commits-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(&repo).unwrap();
+        let commit = |author: &str| {
+            git(
+                &repo,
+                &["-c", "commit.gpgsign=false", "commit", "-q", "--allow-empty", "-m", "work", "--author", author],
+            )
+            .unwrap()
+        };
+        git(&repo, &["init", "-q"]).unwrap();
+        git(&repo, &["config", "user.name", "Sam Lee"]).unwrap();
+        git(&repo, &["config", "user.email", "s.lee@example.com"]).unwrap();
+        commit("Sam Lee <s.lee@example.com>");
+        commit("Someone Else <sxlee@example.com>");
+
+        let now = Local::now();
+        let (start, end) = (now - Duration::hours(1), now + Duration::hours(1));
+        let (author, filter) = author_filter(&repo, None);
+        assert_eq!(author.as_deref(), Some("s.lee@example.com"));
+        assert_eq!(commits(&repo, &filter, &[], start, end).unwrap().len(), 1);
+
+        // A given author is still a pattern
+        let (_, filter) = author_filter(&repo, Some("s.lee@"));
+        assert_eq!(commits(&repo, &filter, &[], start, end).unwrap().len(), 2);
+        std::fs::remove_dir_all(&repo).unwrap();
+    }
+}
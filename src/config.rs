@@ -0,0 +1,649 @@
+use chrono::{Duration, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::api::ApiConfig;
+use crate::calendar::CalendarConfig;
+use crate::goals::Goal;
+use crate::notifier::NotificationConfig;
+use crate::project_resolver::ProjectConfig;
+use crate::scoring::ScoringModel;
+use crate::templates::ReportSettings;
+use crate::timesheet::TimesheetConfig;
+use crate::timewarrior::TimewarriorConfig;
+
+/// Version written by this build. Files with an older (or missing) version are
+/// migrated on load.
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
+/// Config file names searched for in the working directory, in priority order
+const CONFIG_CANDIDATES: [&str; 4] = [
+    "timesense_config.json",
+    "timesense_config.toml",
+    "timesense_config.yaml",
+    "timesense_config.yml",
+];
+
+/// File created when no config exists yet
+pub const DEFAULT_CONFIG_PATH: &str = "timesense_config.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub config_version: u32,
+    pub screenshot_interval_seconds: u64,
+    pub idle_threshold_seconds: u64,
+    pub data_directory: String,
+    /// Time of day at which a new day starts (e.g. "04:00" for night owls).
+    /// Blocks running across it are split between the two days.
+    #[serde(default = "default_day_start")]
+    pub day_start: NaiveTime,
+    /// Longest interruption (another app, idle time or a tracking gap) that
+    /// doesn't end a deep-work session
+    #[serde(default = "default_focus_tolerance_seconds")]
+    pub focus_tolerance_seconds: u64,
+    /// How the Time Distribution Score is computed and rated
+    #[serde(default)]
+    pub scoring: ScoringModel,
+    /// Daily goals and budgets shown in reports and by `timesense goals`
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+    /// Desktop notifications raised while tracking
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    /// Theme and custom templates for the HTML reports
+    #[serde(default)]
+    pub reports: ReportSettings,
+    /// Calendar files whose meetings label the recorded time
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    /// Rounding, merging and project mapping for `timesense timesheet`
+    #[serde(default)]
+    pub timesheet: TimesheetConfig,
+    /// Project mapping and category for `timesense import --format timewarrior`
+    #[serde(default)]
+    pub timewarrior: TimewarriorConfig,
+    /// How editor and terminal time is attributed to git repositories
+    #[serde(default)]
+    pub projects: ProjectConfig,
+    /// Local HTTP API served while tracking
+    #[serde(default)]
+    pub api: ApiConfig,
+    /// Categorization rules, evaluated in order; the first match wins
+    #[serde(default)]
+    pub rules: Vec<CategoryRule>,
+}
+
+/// Assigns `category` to any application whose name contains `app`
+/// (case-insensitive). With `project` set, the rule only applies to time in
+/// that git repository; an empty `app` then matches every application.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryRule {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub app: String,
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Picks the format from the file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse(&self, contents: &str) -> Result<Value, String> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(contents).map(toml_to_json).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn serialize(&self, config: &Config) -> Result<String, String> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(config).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string_pretty(config).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::to_string(config).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Converts a TOML document for the migrations and serde. Native dates and
+/// times become strings, so `day_start = 04:00:00` reads like
+/// `day_start = "04:00:00"`.
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(text) => Value::String(text),
+        toml::Value::Integer(number) => json!(number),
+        toml::Value::Float(number) => json!(number),
+        toml::Value::Boolean(value) => Value::Bool(value),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let productive = ["code", "terminal", "notion", "cursor", "vscode"];
+        let distraction = [
+            "twitter",
+            "youtube",
+            "reddit",
+            "slack",
+            "discord",
+            "spotify",
+            "steam",
+            "twitch",
+            "telegram",
+            "whatsapp",
+            "zoom",
+            "skype",
+            "microsoft teams",
+            "google meet",
+        ];
+
+        let rules = productive
+            .iter()
+            .map(|app| CategoryRule::new(app, "productive"))
+            .chain(
+                distraction
+                    .iter()
+                    .map(|app| CategoryRule::new(app, "distraction")),
+            )
+            .collect();
+
+        Config {
+            config_version: CURRENT_CONFIG_VERSION,
+            screenshot_interval_seconds: 60,
+            idle_threshold_seconds: 180,
+            data_directory: "timesense_data".to_string(),
+            day_start: default_day_start(),
+            focus_tolerance_seconds: default_focus_tolerance_seconds(),
+            scoring: ScoringModel::default(),
+            goals: Vec::new(),
+            notifications: NotificationConfig::default(),
+            reports: ReportSettings::default(),
+            calendar: CalendarConfig::default(),
+            timesheet: TimesheetConfig::default(),
+            timewarrior: TimewarriorConfig::default(),
+            projects: ProjectConfig::default(),
+            api: ApiConfig::default(),
+            rules,
+        }
+    }
+}
+
+fn default_day_start() -> NaiveTime {
+    NaiveTime::MIN
+}
+
+fn default_focus_tolerance_seconds() -> u64 {
+    30
+}
+
+impl Config {
+    pub fn focus_tolerance(&self) -> Duration {
+        Duration::seconds(self.focus_tolerance_seconds as i64)
+    }
+
+    /// Rejects configs that would leave the tracker in a broken state
+    pub fn validate(&self) -> Result<(), String> {
+        if self.screenshot_interval_seconds == 0 {
+            return Err("'screenshot_interval_seconds' must be greater than 0".to_string());
+        }
+        if self.data_directory.trim().is_empty() {
+            return Err("'data_directory' must not be empty".to_string());
+        }
+        for (index, rule) in self.rules.iter().enumerate() {
+            let has_project = rule.project.as_deref().is_some_and(|project| !project.trim().is_empty());
+            if rule.app.trim().is_empty() && !has_project {
+                return Err(format!("rule #{} needs an 'app' or a 'project'", index + 1));
+            }
+            if rule.category.trim().is_empty() {
+                return Err(format!("rule #{} has an empty 'category'", index + 1));
+            }
+        }
+        for goal in &self.goals {
+            goal.validate()?;
+        }
+        self.reports.validate()?;
+        self.calendar.validate()?;
+        self.api.validate()?;
+        self.scoring.validate()
+    }
+}
+
+impl CategoryRule {
+    pub fn new(app: &str, category: &str) -> Self {
+        CategoryRule {
+            app: app.to_lowercase(),
+            category: category.to_string(),
+            project: None,
+        }
+    }
+
+    /// Whether the rule applies to `app_name` while working in `project`
+    pub fn matches(&self, app_name: &str, project: Option<&str>) -> bool {
+        let project_matches = match (&self.project, project) {
+            (None, _) => true,
+            (Some(wanted), Some(project)) => wanted.to_lowercase() == project.to_lowercase(),
+            (Some(_), None) => false,
+        };
+        project_matches && app_name.to_lowercase().contains(&self.app.to_lowercase())
+    }
+
+    /// "code => productive" or "code in timesense => client-work"
+    pub fn describe(&self) -> String {
+        match (&self.project, self.app.is_empty()) {
+            (Some(project), true) => format!("{} => {}", project, self.category),
+            (Some(project), false) => format!("{} in {} => {}", self.app, project, self.category),
+            (None, _) => format!("{} => {}", self.app, self.category),
+        }
+    }
+}
+
+/// A migration upgrades a raw config document from version `n` to `n + 1`
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// Indexed by source version: `MIGRATIONS[0]` upgrades version 1 to 2, and so on
+const MIGRATIONS: [Migration; 1] = [migrate_v1_to_v2];
+
+/// Version 1 kept two flat lists of app names. Version 2 replaces them with an
+/// ordered rule list so new categories can be added without new config keys.
+fn migrate_v1_to_v2(doc: &mut Value) -> Result<(), String> {
+    let object = doc
+        .as_object_mut()
+        .ok_or("config root must be a table/object")?;
+
+    let mut rules = Vec::new();
+    for (key, category) in [
+        ("productive_apps", "productive"),
+        ("distraction_apps", "distraction"),
+    ] {
+        if let Some(apps) = object.remove(key) {
+            let apps = apps
+                .as_array()
+                .ok_or_else(|| format!("'{}' must be a list", key))?;
+            for app in apps {
+                let app = app
+                    .as_str()
+                    .ok_or_else(|| format!("'{}' must only contain strings", key))?;
+                rules.push(json!({ "app": app.to_lowercase(), "category": category }));
+            }
+        }
+    }
+
+    object.insert("rules".to_string(), Value::Array(rules));
+    Ok(())
+}
+
+/// Reads the `config_version` of a raw document, treating a missing field as
+/// version 1 (the last format without the field)
+fn document_version(doc: &Value) -> Result<u32, String> {
+    match doc.get("config_version") {
+        None => Ok(1),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v > 0)
+            .ok_or_else(|| "'config_version' must be a positive integer".to_string()),
+    }
+}
+
+/// Applies every migration needed to bring `doc` to the current version.
+/// Returns the version the document started at.
+fn migrate(doc: &mut Value) -> Result<u32, String> {
+    let original_version = document_version(doc)?;
+    if original_version > CURRENT_CONFIG_VERSION {
+        return Err(format!(
+            "config_version {} is newer than this build supports ({})",
+            original_version, CURRENT_CONFIG_VERSION
+        ));
+    }
+
+    for version in original_version..CURRENT_CONFIG_VERSION {
+        MIGRATIONS[(version - 1) as usize](doc)
+            .map_err(|e| format!("migrating config from v{}: {}", version, e))?;
+    }
+
+    if let Some(object) = doc.as_object_mut() {
+        object.insert("config_version".to_string(), json!(CURRENT_CONFIG_VERSION));
+    }
+
+    Ok(original_version)
+}
+
+/// Finds the config file in the working directory
+pub fn locate() -> Option<PathBuf> {
+    let found: Vec<&str> = CONFIG_CANDIDATES
+        .iter()
+        .copied()
+        .filter(|candidate| Path::new(candidate).exists())
+        .collect();
+
+    if found.len() > 1 {
+        println!(
+            "Multiple config files found ({}); using {}",
+            found.join(", "),
+            found[0]
+        );
+    }
+
+    found.first().map(PathBuf::from)
+}
+
+/// Loads the config at `path`, migrating it if it uses an older schema. A JSON
+/// file is rewritten in place, with the original kept as
+/// `<name>.v<version>.bak`. TOML and YAML files may hold comments that
+/// rewriting would drop, so they are left alone and the migrated config is
+/// written next to them as `<name>.migrated` for the user to review.
+pub fn load(path: &Path) -> Result<Config, String> {
+    let format = ConfigFormat::from_path(path)
+        .ok_or_else(|| format!("Unsupported config file extension: {}", path.display()))?;
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut doc = format
+        .parse(&contents)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    let original_version = migrate(&mut doc)?;
+    let config: Config = serde_json::from_value(doc)
+        .map_err(|e| format!("Invalid config in {}: {}", path.display(), e))?;
+    config
+        .validate()
+        .map_err(|e| format!("Invalid config in {}: {}", path.display(), e))?;
+
+    if original_version < CURRENT_CONFIG_VERSION && format == ConfigFormat::Json {
+        let backup_path = backup_path(path, original_version);
+        fs::copy(path, &backup_path)
+            .map_err(|e| format!("Failed to back up {}: {}", path.display(), e))?;
+        save(&config, path)?;
+        println!(
+            "Migrated {} from config version {} to {} (original saved as {})",
+            path.display(),
+            original_version,
+            CURRENT_CONFIG_VERSION,
+            backup_path.display()
+        );
+    } else if original_version < CURRENT_CONFIG_VERSION {
+        let migrated_path = migrated_path(path);
+        let migrated = format.serialize(&config)?;
+        // Written once; later starts find the same upgrade already there
+        if fs::read_to_string(&migrated_path).ok().as_deref() != Some(migrated.as_str()) {
+            fs::write(&migrated_path, migrated)
+                .map_err(|e| format!("Failed to write {}: {}", migrated_path.display(), e))?;
+        }
+        println!(
+            "{} uses config version {}. Wrote it upgraded to version {} as {}, without comments; \
+             review it and replace {} with it. Until then the file is upgraded at every start.",
+            path.display(),
+            original_version,
+            CURRENT_CONFIG_VERSION,
+            migrated_path.display(),
+            path.display()
+        );
+    }
+
+    Ok(config)
+}
+
+/// Writes the config to `path` in the format implied by its extension
+pub fn save(config: &Config, path: &Path) -> Result<(), String> {
+    let format = ConfigFormat::from_path(path)
+        .ok_or_else(|| format!("Unsupported config file extension: {}", path.display()))?;
+    let contents = format.serialize(config)?;
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Lists human-readable differences between two configs, for logging reloads
+pub fn describe_changes(old: &Config, new: &Config) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if old.screenshot_interval_seconds != new.screenshot_interval_seconds {
+        changes.push(format!(
+            "screenshot_interval_seconds: {} -> {}",
+            old.screenshot_interval_seconds, new.screenshot_interval_seconds
+        ));
+    }
+    if old.idle_threshold_seconds != new.idle_threshold_seconds {
+        changes.push(format!(
+            "idle_threshold_seconds: {} -> {}",
+            old.idle_threshold_seconds, new.idle_threshold_seconds
+        ));
+    }
+    if old.data_directory != new.data_directory {
+        changes.push(format!(
+            "data_directory: {} -> {}",
+            old.data_directory, new.data_directory
+        ));
+    }
+    if old.day_start != new.day_start {
+        changes.push(format!("day_start: {} -> {}", old.day_start, new.day_start));
+    }
+    if old.focus_tolerance_seconds != new.focus_tolerance_seconds {
+        changes.push(format!(
+            "focus_tolerance_seconds: {} -> {}",
+            old.focus_tolerance_seconds, new.focus_tolerance_seconds
+        ));
+    }
+    if old.scoring != new.scoring {
+        changes.push("scoring model changed".to_string());
+    }
+    if old.reports != new.reports {
+        changes.push("report settings changed".to_string());
+    }
+    if old.notifications != new.notifications {
+        changes.push("notification settings changed".to_string());
+    }
+    if old.calendar != new.calendar {
+        changes.push("calendar settings changed".to_string());
+    }
+    if old.timesheet != new.timesheet {
+        changes.push("timesheet settings changed".to_string());
+    }
+    if old.timewarrior != new.timewarrior {
+        changes.push("timewarrior settings changed".to_string());
+    }
+    if old.projects != new.projects {
+        changes.push("project settings changed".to_string());
+    }
+    if old.api != new.api {
+        changes.push("API settings changed".to_string());
+    }
+    for goal in &old.goals {
+        if !new.goals.iter().any(|other| other.name == goal.name) {
+            changes.push(format!("goal removed: {}", goal.name));
+        }
+    }
+    for goal in &new.goals {
+        match old.goals.iter().find(|other| other.name == goal.name) {
+            None => changes.push(format!("goal added: {} ({})", goal.name, goal.describe())),
+            Some(other) if other != goal => {
+                changes.push(format!("goal changed: {} ({})", goal.name, goal.describe()))
+            }
+            Some(_) => {}
+        }
+    }
+
+    for rule in &old.rules {
+        if !new.rules.contains(rule) {
+            changes.push(format!("rule removed: {}", rule.describe()));
+        }
+    }
+    for rule in &new.rules {
+        if !old.rules.contains(rule) {
+            changes.push(format!("rule added: {}", rule.describe()));
+        }
+    }
+    if changes.is_empty() && old.rules != new.rules {
+        changes.push("rules reordered".to_string());
+    }
+
+    changes
+}
+
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.v{}.bak", file_name, version))
+}
+
+fn migrated_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.migrated", file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for config files of one test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("timesense-config-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn expected_rules() -> Vec<CategoryRule> {
+        let rule = |app: &str, category: &str| CategoryRule {
+            app: app.to_string(),
+            category: category.to_string(),
+            project: None,
+        };
+        vec![rule("code", "productive"), rule("slack", "distraction")]
+    }
+
+    #[test]
+    fn v1_app_lists_become_rules() {
+        let mut doc = json!({
+            "screenshot_interval_seconds": 10,
+            "productive_apps": ["Code"],
+            "distraction_apps": ["slack"],
+        });
+        migrate_v1_to_v2(&mut doc).unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "screenshot_interval_seconds": 10,
+                "rules": [
+                    { "app": "code", "category": "productive" },
+                    { "app": "slack", "category": "distraction" },
+                ],
+            })
+        );
+
+        let mut doc = json!({ "productive_apps": "code" });
+        assert!(migrate_v1_to_v2(&mut doc).is_err());
+    }
+
+    #[test]
+    fn versions_must_be_positive_and_known() {
+        assert_eq!(migrate(&mut json!({})), Ok(1));
+        assert_eq!(migrate(&mut json!({ "config_version": 2 })), Ok(2));
+        assert!(migrate(&mut json!({ "config_version": 0 })).is_err());
+        assert!(migrate(&mut json!({ "config_version": -1 })).is_err());
+        assert!(migrate(&mut json!({ "config_version": 3 })).is_err());
+    }
+
+    #[test]
+    fn json_configs_are_migrated_in_place_with_a_backup() {
+        let dir = test_dir("json");
+        let path = dir.join("timesense_config.json");
+        let original = r#"{
+            "screenshot_interval_seconds": 10,
+            "idle_threshold_seconds": 300,
+            "data_directory": "data",
+            "productive_apps": ["code"],
+            "distraction_apps": ["slack"]
+        }"#;
+        fs::write(&path, original).unwrap();
+
+        let config = load(&path).unwrap();
+        assert_eq!(config.rules, expected_rules());
+        assert_eq!(fs::read_to_string(dir.join("timesense_config.json.v1.bak")).unwrap(), original);
+        let rewritten: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten["config_version"], json!(CURRENT_CONFIG_VERSION));
+        assert!(rewritten.get("productive_apps").is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn toml_configs_keep_their_comments_and_get_a_migrated_copy() {
+        let dir = test_dir("toml");
+        let path = dir.join("timesense_config.toml");
+        let original = "# Sampling\n\
+            screenshot_interval_seconds = 10\n\
+            idle_threshold_seconds = 300\n\
+            data_directory = \"data\"\n\
+            productive_apps = [\"code\"] # editors\n\
+            distraction_apps = [\"slack\"]\n";
+        fs::write(&path, original).unwrap();
+
+        let config = load(&path).unwrap();
+        assert_eq!(config.rules, expected_rules());
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+        assert!(!dir.join("timesense_config.toml.v1.bak").exists());
+        let migrated = fs::read_to_string(dir.join("timesense_config.toml.migrated")).unwrap();
+        let migrated: Config = toml::from_str(&migrated).unwrap();
+        assert_eq!(migrated.config_version, CURRENT_CONFIG_VERSION);
+        assert_eq!(migrated.rules, expected_rules());
+
+        // The next start leaves the identical migrated copy alone
+        let migrated_path = dir.join("timesense_config.toml.migrated");
+        let earlier = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::options().write(true).open(&migrated_path).unwrap().set_modified(earlier).unwrap();
+        load(&path).unwrap();
+        assert_eq!(fs::metadata(&migrated_path).unwrap().modified().unwrap(), earlier);
+
+        // A changed original is written out again
+        fs::write(&path, original.replace("= 10", "= 20")).unwrap();
+        load(&path).unwrap();
+        assert_ne!(fs::metadata(&migrated_path).unwrap().modified().unwrap(), earlier);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn toml_times_may_be_written_without_quotes() {
+        let dir = test_dir("toml-time");
+        let path = dir.join("timesense_config.toml");
+        let required = "config_version = 2\n\
+            screenshot_interval_seconds = 10\n\
+            idle_threshold_seconds = 300\n\
+            data_directory = \"data\"\n";
+        fs::write(&path, format!("{}day_start = 04:00:00\n", required)).unwrap();
+        let config = load(&path).unwrap();
+        assert_eq!(config.day_start, NaiveTime::from_hms_opt(4, 0, 0).unwrap());
+
+        fs::write(&path, format!("{}day_start = \"04:30:00\"\n", required)).unwrap();
+        let config = load(&path).unwrap();
+        assert_eq!(config.day_start, NaiveTime::from_hms_opt(4, 30, 0).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
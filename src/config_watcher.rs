@@ -0,0 +1,133 @@
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Raises a reload flag when the config file changes on disk or when the
+/// process receives SIGHUP. The tracking loop polls the flag between samples.
+pub struct ConfigWatcher {
+    reload_requested: Arc<AtomicBool>,
+    // Kept alive for as long as the watcher exists; dropping it stops the watch
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl ConfigWatcher {
+    pub fn new(config_path: &Path) -> Self {
+        let reload_requested = Arc::new(AtomicBool::new(false));
+
+        #[cfg(unix)]
+        if let Err(e) = signal_hook::flag::register(
+            signal_hook::consts::SIGHUP,
+            reload_requested.clone(),
+        ) {
+            println!("Failed to register SIGHUP handler: {}", e);
+        }
+
+        let watcher = match Self::watch_file(config_path, reload_requested.clone()) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                println!(
+                    "Failed to watch {} for changes: {}",
+                    config_path.display(),
+                    e
+                );
+                None
+            }
+        };
+
+        ConfigWatcher {
+            reload_requested,
+            _watcher: watcher,
+        }
+    }
+
+    /// Watches the containing directory rather than the file itself, since
+    /// most editors save by writing a new file and renaming it over the old one
+    fn watch_file(
+        config_path: &Path,
+        reload_requested: Arc<AtomicBool>,
+    ) -> notify::Result<RecommendedWatcher> {
+        let file_name = config_path.file_name().map(|name| name.to_os_string());
+        let directory = match config_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => Path::new(".").to_path_buf(),
+        };
+
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                let event = match result {
+                    Ok(event) => event,
+                    Err(_) => return,
+                };
+                if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    return;
+                }
+                let touches_config = event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name() == file_name.as_deref());
+                if touches_config {
+                    reload_requested.store(true, Ordering::SeqCst);
+                }
+            })?;
+        watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+
+        Ok(watcher)
+    }
+
+    /// Returns true (once) if a reload was requested since the last call
+    pub fn take_reload_request(&self) -> bool {
+        self.reload_requested.swap(false, Ordering::SeqCst)
+    }
+
+    pub fn reload_pending(&self) -> bool {
+        self.reload_requested.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, thread, time};
+
+    /// Waits up to five seconds for the watcher to ask for a reload
+    fn reload_requested(watcher: &ConfigWatcher) -> bool {
+        for _ in 0..50 {
+            if watcher.take_reload_request() {
+                return true;
+            }
+            thread::sleep(time::Duration::from_millis(100));
+        }
+        false
+    }
+
+    #[test]
+    fn changes_to_the_config_file_request_a_reload() {
+        let dir = std::env::temp_dir().join(format!("timesense-watcher-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("timesense_config.json");
+        fs::write(&path, "{}").unwrap();
+
+        let watcher = ConfigWatcher::new(&path);
+        thread::sleep(time::Duration::from_millis(200));
+        watcher.take_reload_request();
+
+        // Other files in the directory are ignored
+        fs::write(dir.join("notes.txt"), "unrelated").unwrap();
+        thread::sleep(time::Duration::from_millis(500));
+        assert!(!watcher.take_reload_request());
+
+        fs::write(&path, r#"{"screenshot_interval_seconds": 5}"#).unwrap();
+        assert!(reload_requested(&watcher));
+        // The request is taken once
+        assert!(!watcher.take_reload_request());
+
+        // Saved by writing a new file and renaming it over the config
+        let replacement = dir.join("timesense_config.json.new");
+        fs::write(&replacement, "{}").unwrap();
+        fs::rename(&replacement, &path).unwrap();
+        assert!(reload_requested(&watcher));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,498 @@
+use chrono::{DateTime, Duration, Local};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time;
+
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+#[cfg(unix)]
+use crate::api;
+use crate::config::Config;
+use crate::notifier::Notifier;
+use crate::time_format::{format_duration, parse_duration};
+use crate::TimeBlock;
+
+/// Tag of the blocks recorded during a focus session
+pub const FOCUS_TAG: &str = "focus";
+
+/// Longest pause or focus session
+const MAX_LENGTH: Duration = Duration::hours(24);
+
+/// How long the socket waits for the tracker loop to act on a command
+#[cfg(unix)]
+const REPLY_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+/// A command for the running tracker. On the socket, each is one line of
+/// text, answered by one line starting with `ok: ` or `error: `.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    /// Stop recording for `length`, or until resumed
+    Pause { length: Option<Duration> },
+    Resume,
+    /// Tag every block from now on with `label`; `None` stops tagging
+    Tag { label: Option<String> },
+    /// Start a focus session of `length`
+    StartFocus { length: Duration },
+    /// Stop the tracker as Ctrl+C does
+    Stop,
+}
+
+/// A pause or focus session length as written on the command line, such as
+/// "30m" or "1h30m", up to 24 hours
+pub fn parse_length(text: &str) -> Result<Duration, String> {
+    check_length(parse_duration(text)?)
+}
+
+fn check_length(length: Duration) -> Result<Duration, String> {
+    if length > MAX_LENGTH {
+        return Err(format!("{} is longer than the limit of {}", format_duration(length), format_duration(MAX_LENGTH)));
+    }
+    Ok(length)
+}
+
+impl ControlCommand {
+    #[cfg(unix)]
+    fn to_line(&self) -> String {
+        match self {
+            ControlCommand::Pause { length: None } => "pause".to_string(),
+            ControlCommand::Pause { length: Some(length) } => format!("pause {}", length.num_seconds()),
+            ControlCommand::Resume => "resume".to_string(),
+            ControlCommand::Tag { label: None } => "tag".to_string(),
+            ControlCommand::Tag { label: Some(label) } => format!("tag {}", label.replace(['\r', '\n'], " ")),
+            ControlCommand::StartFocus { length } => format!("start-focus {}", length.num_seconds()),
+            ControlCommand::Stop => "stop".to_string(),
+        }
+    }
+
+    /// Parses a line written by `to_line`; lengths are in seconds
+    #[cfg(unix)]
+    fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (name, argument) = match line.split_once(' ') {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (line, None),
+        };
+        let seconds = |argument: &str| {
+            argument
+                .parse::<i64>()
+                .ok()
+                .filter(|seconds| *seconds > 0)
+                .and_then(Duration::try_seconds)
+                .ok_or_else(|| format!("Invalid length in seconds: {}", argument))
+                .and_then(check_length)
+        };
+
+        match (name, argument) {
+            ("pause", None) => Ok(ControlCommand::Pause { length: None }),
+            ("pause", Some(argument)) => Ok(ControlCommand::Pause {
+                length: Some(seconds(argument)?),
+            }),
+            ("resume", None) => Ok(ControlCommand::Resume),
+            ("tag", label) => Ok(ControlCommand::Tag {
+                label: label.filter(|label| !label.is_empty()).map(str::to_string),
+            }),
+            ("start-focus", Some(argument)) => Ok(ControlCommand::StartFocus {
+                length: seconds(argument)?,
+            }),
+            ("stop", None) => Ok(ControlCommand::Stop),
+            _ => Err(format!("Unknown command: {}", line)),
+        }
+    }
+}
+
+/// A focus session started with `timesense start-focus`
+#[derive(Debug, Clone)]
+pub struct FocusSession {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    /// Start of the last distracting block a notification was sent for
+    alerted: Option<DateTime<Local>>,
+}
+
+/// What scripts have changed about the running tracker
+#[derive(Debug, Default)]
+pub struct ControlState {
+    paused: bool,
+    /// End of a pause with a length
+    paused_until: Option<DateTime<Local>>,
+    tag: Option<String>,
+    focus: Option<FocusSession>,
+}
+
+impl ControlState {
+    /// Applies a command and returns the answer for the client. Stopping is
+    /// left to the caller.
+    pub fn apply(&mut self, command: &ControlCommand, now: DateTime<Local>) -> Result<String, String> {
+        let end_after = |length: Duration| {
+            now.checked_add_signed(check_length(length)?)
+                .ok_or_else(|| format!("{} from now is out of range", format_duration(length)))
+        };
+
+        let answer = match command {
+            ControlCommand::Pause { length: Some(length) } => {
+                let until = end_after(*length)?;
+                self.paused = true;
+                self.paused_until = Some(until);
+                format!("Paused until {}", until.format("%H:%M"))
+            }
+            ControlCommand::Pause { length: None } => {
+                self.paused = true;
+                self.paused_until = None;
+                "Paused until resumed".to_string()
+            }
+            ControlCommand::Resume if !self.paused => "Not paused".to_string(),
+            ControlCommand::Resume => {
+                self.paused = false;
+                self.paused_until = None;
+                "Resumed".to_string()
+            }
+            ControlCommand::Tag { label: Some(label) } => {
+                self.tag = Some(label.clone());
+                format!("Tagging blocks with '{}'", label)
+            }
+            ControlCommand::Tag { label: None } => {
+                self.tag = None;
+                "Stopped tagging blocks".to_string()
+            }
+            ControlCommand::StartFocus { length } => {
+                let end = end_after(*length)?;
+                self.focus = Some(FocusSession {
+                    start: now,
+                    end,
+                    alerted: None,
+                });
+                format!("Focus session until {}", end.format("%H:%M"))
+            }
+            ControlCommand::Stop => "Stopping".to_string(),
+        };
+        Ok(answer)
+    }
+
+    /// Whether recording is paused. A pause whose time is up ends here.
+    pub fn is_paused(&mut self, now: DateTime<Local>) -> bool {
+        if self.paused_until.is_some_and(|until| now >= until) {
+            self.paused = false;
+            self.paused_until = None;
+            println!("Pause over, tracking again");
+        }
+        self.paused
+    }
+
+    /// Tags of a block recorded at `now`
+    pub fn tags(&self, now: DateTime<Local>) -> Vec<String> {
+        let mut tags: Vec<String> = self.tag.iter().cloned().collect();
+        if self.focus.as_ref().is_some_and(|focus| now < focus.end) {
+            tags.push(FOCUS_TAG.to_string());
+        }
+        tags
+    }
+
+    /// During a focus session, notifies once per distracting block; when the
+    /// session is over, notifies how it went and ends it
+    pub fn check_focus(&mut self, config: &Config, blocks: &[TimeBlock], now: DateTime<Local>, notifier: &mut Notifier) {
+        let Some(focus) = self.focus.as_mut() else {
+            return;
+        };
+
+        let notification = if now >= focus.end {
+            let time_in = |category: &str| {
+                blocks
+                    .iter()
+                    .filter(|block| !block.idle && block.activity_type == category)
+                    .fold(Duration::zero(), |total, block| {
+                        let overlap = block
+                            .end_time
+                            .min(focus.end)
+                            .signed_duration_since(block.start_time.max(focus.start));
+                        total + overlap.max(Duration::zero())
+                    })
+            };
+            let summary = (
+                "Focus session over".to_string(),
+                format!(
+                    "{} focused and {} distracted in {}",
+                    format_duration(time_in("productive")),
+                    format_duration(time_in("distraction")),
+                    format_duration(focus.end.signed_duration_since(focus.start))
+                ),
+            );
+            self.focus = None;
+            Some(summary)
+        } else {
+            match blocks.last() {
+                Some(block)
+                    if !block.idle
+                        && block.activity_type == "distraction"
+                        && focus.alerted != Some(block.start_time) =>
+                {
+                    focus.alerted = Some(block.start_time);
+                    Some((
+                        "Focus session running".to_string(),
+                        format!(
+                            "{} is a distraction; {} of focus left",
+                            block.application,
+                            format_duration(focus.end.signed_duration_since(now))
+                        ),
+                    ))
+                }
+                _ => None,
+            }
+        };
+
+        if let Some((summary, body)) = notification {
+            if !config.notifications.enabled {
+                println!("{}: {}", summary, body);
+            } else if let Err(e) = notifier.send(&summary, &body) {
+                println!("{}", e);
+            }
+        }
+    }
+}
+
+/// The control socket of the tracker using `config`
+pub fn socket_path(config: &Config) -> PathBuf {
+    Path::new(&config.data_directory).join("control.sock")
+}
+
+/// A command received on the socket, waiting for the tracker's answer
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    reply: Sender<Result<String, String>>,
+}
+
+impl ControlRequest {
+    pub fn reply(self, answer: Result<String, String>) {
+        // The client may have given up waiting
+        let _ = self.reply.send(answer);
+    }
+}
+
+/// The listening control socket; dropping it closes and removes the socket
+pub struct ControlSocket {
+    path: PathBuf,
+    receiver: Receiver<ControlRequest>,
+    pending: Vec<ControlRequest>,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ControlSocket {
+    /// Listens at `path`. Failures (such as another tracker using the
+    /// socket) are logged and leave the socket closed.
+    pub fn start(path: &Path) -> Option<ControlSocket> {
+        match ControlSocket::bind(path) {
+            Ok(socket) => {
+                println!("Control socket at {}", path.display());
+                Some(socket)
+            }
+            Err(e) => {
+                println!("Failed to open the control socket: {}", e);
+                None
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn bind(path: &Path) -> Result<ControlSocket, String> {
+        let listener = api::bind_private_socket(path, |bound| UnixListener::bind(bound).map_err(|e| e.to_string()))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let stopping = Arc::new(AtomicBool::new(false));
+        let thread_stopping = stopping.clone();
+        let thread = std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(stream) => serve(stream, &sender),
+                    Err(e) => println!("Control connection failed: {}", e),
+                }
+            }
+        });
+
+        Ok(ControlSocket {
+            path: path.to_path_buf(),
+            receiver,
+            pending: Vec::new(),
+            stopping,
+            thread: Some(thread),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn bind(_path: &Path) -> Result<ControlSocket, String> {
+        Err("The control socket is only available on Unix".to_string())
+    }
+
+    /// Whether commands are waiting to be handled
+    pub fn poll(&mut self) -> bool {
+        self.pending.extend(self.receiver.try_iter());
+        !self.pending.is_empty()
+    }
+
+    /// The commands received since the last call, oldest first
+    pub fn take_requests(&mut self) -> Vec<ControlRequest> {
+        self.poll();
+        std::mem::take(&mut self.pending)
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        // Unanswered requests fail right away instead of timing out
+        self.poll();
+        self.pending.clear();
+
+        self.stopping.store(true, Ordering::SeqCst);
+        // Wake the listener thread so it sees the flag
+        #[cfg(unix)]
+        let _ = UnixStream::connect(&self.path);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Reads one command from a client, hands it to the tracker loop and writes
+/// back the answer
+#[cfg(unix)]
+fn serve(stream: UnixStream, sender: &Sender<ControlRequest>) {
+    let _ = stream.set_read_timeout(Some(time::Duration::from_secs(5)));
+    let mut line = String::new();
+    let answer = match BufReader::new(&stream).read_line(&mut line) {
+        Err(e) => format!("error: {}", e),
+        // The wake-up connection at shutdown sends nothing
+        Ok(0) => return,
+        Ok(_) => match ControlCommand::parse(&line) {
+            Err(e) => format!("error: {}", e),
+            Ok(command) => {
+                let (reply, answer) = mpsc::channel();
+                if sender.send(ControlRequest { command, reply }).is_err() {
+                    "error: the tracker is shutting down".to_string()
+                } else {
+                    match answer.recv_timeout(REPLY_TIMEOUT) {
+                        Ok(Ok(answer)) => format!("ok: {}", answer),
+                        Ok(Err(e)) => format!("error: {}", e),
+                        Err(_) => "error: the tracker did not answer".to_string(),
+                    }
+                }
+            }
+        },
+    };
+    let _ = (&stream).write_all(format!("{}\n", answer).as_bytes());
+}
+
+/// Sends `command` to the running tracker and prints its answer
+#[cfg(unix)]
+pub fn send(config: &Config, command: &ControlCommand) -> Result<(), String> {
+    let path = socket_path(config);
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|_| format!("TimeSense is not running (no control socket at {})", path.display()))?;
+    stream
+        .write_all(format!("{}\n", command.to_line()).as_bytes())
+        .map_err(|e| format!("Failed to send the command: {}", e))?;
+
+    let mut answer = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut answer)
+        .map_err(|e| format!("Failed to read the answer: {}", e))?;
+    let answer = answer.trim();
+    match answer.strip_prefix("ok: ") {
+        Some(answer) => {
+            println!("{}", answer);
+            Ok(())
+        }
+        None => Err(answer.strip_prefix("error: ").unwrap_or(answer).to_string()),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send(_config: &Config, _command: &ControlCommand) -> Result<(), String> {
+    Err("The control socket is only available on Unix".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn lengths_are_capped() {
+        assert_eq!(parse_length("1h30m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_length("24h"), Ok(Duration::hours(24)));
+        assert!(parse_length("25h").is_err());
+        assert!(parse_length("10000000000h").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn live_sockets_and_other_files_are_left_alone() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("timesense-control-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("control.sock");
+
+        // A stale socket is replaced, and the new one is private
+        drop(UnixListener::bind(&path).unwrap());
+        let socket = ControlSocket::bind(&path).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        let error = ControlSocket::bind(&path).err().unwrap();
+        assert!(error.contains("in use"), "{}", error);
+        drop(socket);
+
+        fs::write(&path, "notes").unwrap();
+        let error = ControlSocket::bind(&path).err().unwrap();
+        assert!(error.contains("not a socket"), "{}", error);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "notes");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_lines_with_huge_lengths_are_rejected() {
+        assert_eq!(
+            ControlCommand::parse("pause 600"),
+            Ok(ControlCommand::Pause { length: Some(Duration::minutes(10)) })
+        );
+        assert!(ControlCommand::parse("pause 9223372036854776").is_err());
+        assert!(ControlCommand::parse("pause 9223372036854775807").is_err());
+        assert!(ControlCommand::parse("start-focus 86401").is_err());
+        assert!(ControlCommand::parse("pause 0").is_err());
+    }
+
+    #[test]
+    fn apply_rejects_lengths_out_of_range() {
+        let mut state = ControlState::default();
+        let pause = ControlCommand::Pause { length: Some(Duration::days(365 * 1_000_000)) };
+        assert!(state.apply(&pause, now()).is_err());
+        assert!(!state.is_paused(now()));
+
+        let focus = ControlCommand::StartFocus { length: Duration::days(365 * 1_000_000) };
+        assert!(state.apply(&focus, now()).is_err());
+        assert!(state.tags(now()).is_empty());
+    }
+
+    #[test]
+    fn pause_ends_on_time() {
+        let mut state = ControlState::default();
+        let pause = ControlCommand::Pause { length: Some(Duration::minutes(30)) };
+        assert_eq!(state.apply(&pause, now()), Ok("Paused until 09:30".to_string()));
+        assert!(state.is_paused(now() + Duration::minutes(29)));
+        assert!(!state.is_paused(now() + Duration::minutes(30)));
+    }
+}
@@ -0,0 +1,541 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::BlocksAction;
+use crate::config::Config;
+use crate::storage;
+use crate::time_format::format_duration;
+use crate::TimeBlock;
+
+/// A manual change layered on top of the recorded blocks of a day.
+///
+/// Corrections are stored separately from the raw blocks and re-applied in
+/// order every time the day is read, so re-categorizing the raw data never
+/// loses them. Blocks are addressed by their start time, which stays stable
+/// across re-categorization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Correction {
+    Reassign {
+        block_start: DateTime<Local>,
+        category: Option<String>,
+        project: Option<String>,
+    },
+    Split {
+        at: DateTime<Local>,
+    },
+    Merge {
+        first_start: DateTime<Local>,
+        last_start: DateTime<Local>,
+    },
+    Note {
+        block_start: DateTime<Local>,
+        text: String,
+    },
+    Tag {
+        block_start: DateTime<Local>,
+        tags: Vec<String>,
+    },
+    /// Time that wasn't tracked on the computer. Replaces whatever was
+    /// recorded in the same window.
+    Manual {
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        label: String,
+        category: String,
+        project: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        note: Option<String>,
+    },
+}
+
+pub fn corrections_path(data_directory: &str, date: NaiveDate) -> PathBuf {
+    Path::new(data_directory).join(format!("corrections_{}.json", date.format("%Y-%m-%d")))
+}
+
+pub fn load(data_directory: &str, date: NaiveDate) -> Result<Vec<Correction>, String> {
+    let path = corrections_path(data_directory, date);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+pub fn save(data_directory: &str, date: NaiveDate, corrections: &[Correction]) -> Result<(), String> {
+    let path = corrections_path(data_directory, date);
+    let json = serde_json::to_string_pretty(corrections).map_err(|e| e.to_string())?;
+
+    // Write to a temporary file first so a crash never loses the corrections
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Applies the corrections, in order, to a day's raw blocks. Corrections whose
+/// target block no longer exists are skipped.
+pub fn apply(mut blocks: Vec<TimeBlock>, corrections: &[Correction]) -> Vec<TimeBlock> {
+    for correction in corrections {
+        match correction {
+            Correction::Reassign {
+                block_start,
+                category,
+                project,
+            } => {
+                if let Some(block) = find_block(&mut blocks, *block_start) {
+                    if let Some(category) = category {
+                        block.activity_type = category.clone();
+                        // Reassigning a category asserts the time was spent on it
+                        block.idle = false;
+                    }
+                    if let Some(project) = project {
+                        block.project = Some(project.clone());
+                        block.branch = None;
+                    }
+                }
+            }
+            Correction::Split { at } => {
+                if let Some(index) = blocks
+                    .iter()
+                    .position(|block| block.start_time < *at && *at < block.end_time)
+                {
+                    let mut second = blocks[index].clone();
+                    second.start_time = *at;
+                    blocks[index].end_time = *at;
+                    blocks.insert(index + 1, second);
+                }
+            }
+            Correction::Merge {
+                first_start,
+                last_start,
+            } => merge_blocks(&mut blocks, *first_start, *last_start),
+            Correction::Note { block_start, text } => {
+                if let Some(block) = find_block(&mut blocks, *block_start) {
+                    block.notes.push(text.clone());
+                }
+            }
+            Correction::Tag { block_start, tags } => {
+                if let Some(block) = find_block(&mut blocks, *block_start) {
+                    for tag in tags {
+                        if !block.tags.contains(tag) {
+                            block.tags.push(tag.clone());
+                        }
+                    }
+                }
+            }
+            Correction::Manual {
+                start,
+                end,
+                label,
+                category,
+                project,
+                tags,
+                note,
+            } => {
+                carve_out(&mut blocks, *start, *end);
+                blocks.push(TimeBlock {
+                    start_time: *start,
+                    end_time: *end,
+                    application: label.clone(),
+                    activity_type: category.clone(),
+                    idle: false,
+                    window_title: None,
+                    project: project.clone(),
+                    branch: None,
+                    tags: tags.clone(),
+                    notes: note.iter().cloned().collect(),
+                    manual: true,
+                    meeting: None,
+                });
+                blocks.sort_by_key(|block| block.start_time);
+            }
+        }
+    }
+
+    blocks
+}
+
+fn find_block(blocks: &mut [TimeBlock], start: DateTime<Local>) -> Option<&mut TimeBlock> {
+    blocks.iter_mut().find(|block| block.start_time == start)
+}
+
+/// Replaces the blocks from `first_start` to `last_start` with a single block
+/// that takes its application and category from the longest of them
+fn merge_blocks(blocks: &mut Vec<TimeBlock>, first_start: DateTime<Local>, last_start: DateTime<Local>) {
+    let first = blocks.iter().position(|block| block.start_time == first_start);
+    let last = blocks.iter().position(|block| block.start_time == last_start);
+    let (first, last) = match (first, last) {
+        (Some(first), Some(last)) if first < last => (first, last),
+        _ => return,
+    };
+
+    let merged_range: Vec<TimeBlock> = blocks.drain(first..=last).collect();
+    let mut merged = merged_range
+        .iter()
+        .max_by_key(|block| block.end_time.signed_duration_since(block.start_time))
+        .cloned()
+        .unwrap();
+    merged.start_time = merged_range[0].start_time;
+    merged.end_time = merged_range[merged_range.len() - 1].end_time;
+    merged.tags.clear();
+    merged.notes.clear();
+    for block in &merged_range {
+        for tag in &block.tags {
+            if !merged.tags.contains(tag) {
+                merged.tags.push(tag.clone());
+            }
+        }
+        merged.notes.extend(block.notes.iter().cloned());
+    }
+
+    blocks.insert(first, merged);
+}
+
+/// Removes the `start..end` window from the recorded blocks, trimming or
+/// splitting any block that overlaps it
+fn carve_out(blocks: &mut Vec<TimeBlock>, start: DateTime<Local>, end: DateTime<Local>) {
+    let mut result = Vec::with_capacity(blocks.len() + 1);
+    for block in blocks.drain(..) {
+        if block.end_time <= start || block.start_time >= end {
+            result.push(block);
+            continue;
+        }
+        if block.start_time < start {
+            let mut before = block.clone();
+            before.end_time = start;
+            result.push(before);
+        }
+        if block.end_time > end {
+            let mut after = block;
+            after.start_time = end;
+            result.push(after);
+        }
+    }
+    *blocks = result;
+}
+
+/// Handles the `timesense blocks` subcommands
+pub fn run(config: &Config, date: NaiveDate, action: BlocksAction) -> Result<(), String> {
+    let blocks = storage::load_effective_blocks(&config.data_directory, date)?;
+
+    let correction = match action {
+        BlocksAction::List => {
+            print_blocks(date, &blocks);
+            return Ok(());
+        }
+        BlocksAction::Undo => {
+            let mut corrections = load(&config.data_directory, date)?;
+            match corrections.pop() {
+                Some(removed) => {
+                    save(&config.data_directory, date, &corrections)?;
+                    println!("Removed correction: {:?}", removed);
+                    crate::regenerate_day(config, date)?;
+                }
+                None => println!("No corrections recorded for {}", date),
+            }
+            return Ok(());
+        }
+        BlocksAction::Reassign {
+            index,
+            category,
+            project,
+        } => {
+            if category.is_none() && project.is_none() {
+                return Err("Nothing to change: pass --category and/or --project".to_string());
+            }
+            Correction::Reassign {
+                block_start: block_at(&blocks, index)?.start_time,
+                category,
+                project,
+            }
+        }
+        BlocksAction::Split { index, at } => {
+            let block = block_at(&blocks, index)?;
+            let at = local_time(date, at, config.day_start)?;
+            if at <= block.start_time || at >= block.end_time {
+                return Err(format!(
+                    "{} is not inside block {} ({} - {})",
+                    at.format("%H:%M:%S"),
+                    index,
+                    block.start_time.format("%H:%M:%S"),
+                    block.end_time.format("%H:%M:%S")
+                ));
+            }
+            Correction::Split { at }
+        }
+        BlocksAction::Merge { first, last } => {
+            if first >= last {
+                return Err("The first block must come before the last block".to_string());
+            }
+            Correction::Merge {
+                first_start: block_at(&blocks, first)?.start_time,
+                last_start: block_at(&blocks, last)?.start_time,
+            }
+        }
+        BlocksAction::Note { index, text } => Correction::Note {
+            block_start: block_at(&blocks, index)?.start_time,
+            text,
+        },
+        BlocksAction::Tag { index, tags } => Correction::Tag {
+            block_start: block_at(&blocks, index)?.start_time,
+            tags,
+        },
+        BlocksAction::Add {
+            start,
+            end,
+            label,
+            category,
+            project,
+            tags,
+            note,
+        } => {
+            let start = local_time(date, start, config.day_start)?;
+            let end = local_time(date, end, config.day_start)?;
+            if end <= start {
+                return Err("--end must be after --start".to_string());
+            }
+            Correction::Manual {
+                start,
+                end,
+                label,
+                category,
+                project,
+                tags,
+                note,
+            }
+        }
+    };
+
+    let mut corrections = load(&config.data_directory, date)?;
+    corrections.push(correction);
+    save(&config.data_directory, date, &corrections)?;
+
+    let updated = apply(storage::load_blocks(&config.data_directory, date)?, &corrections);
+    print_blocks(date, &updated);
+    crate::regenerate_day(config, date)?;
+
+    Ok(())
+}
+
+/// Looks up a block by its 1-based position in `timesense blocks list`
+fn block_at(blocks: &[TimeBlock], index: usize) -> Result<&TimeBlock, String> {
+    index
+        .checked_sub(1)
+        .and_then(|i| blocks.get(i))
+        .ok_or_else(|| format!("No block #{} (the day has {} blocks)", index, blocks.len()))
+}
+
+/// The moment `time` falls on during the logical day `date`; times before
+/// `day_start` are on the calendar day after `date`
+fn local_time(date: NaiveDate, time: NaiveTime, day_start: NaiveTime) -> Result<DateTime<Local>, String> {
+    let date = if time < day_start { date + Duration::days(1) } else { date };
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .ok_or_else(|| format!("{} {} does not exist in the local time zone", date, time))
+}
+
+fn print_blocks(date: NaiveDate, blocks: &[TimeBlock]) {
+    if blocks.is_empty() {
+        println!("No blocks recorded for {}", date);
+        return;
+    }
+
+    println!("Blocks for {}:", date);
+    for (i, block) in blocks.iter().enumerate() {
+        let mut line = format!(
+            "{:>3}. {} - {} {:>8}  {} [{}{}]",
+            i + 1,
+            block.start_time.format("%H:%M:%S"),
+            block.end_time.format("%H:%M:%S"),
+            format_duration(block.end_time.signed_duration_since(block.start_time)),
+            block.application,
+            block.activity_type,
+            if block.idle { ", idle" } else { "" }
+        );
+        if let Some(project) = &block.project {
+            line.push_str(&format!(" project: {}", project));
+            if let Some(branch) = &block.branch {
+                line.push_str(&format!(" ({})", branch));
+            }
+        }
+        if !block.tags.is_empty() {
+            line.push_str(&format!(" tags: {}", block.tags.join(", ")));
+        }
+        if block.manual {
+            line.push_str(" (manual)");
+        }
+        println!("{}", line);
+        for note in &block.notes {
+            println!("       note: {}", note);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 19, hour, minute, 0).unwrap()
+    }
+
+    fn block(start: DateTime<Local>, end: DateTime<Local>, application: &str, activity_type: &str) -> TimeBlock {
+        let mut block = TimeBlock::new(start, application.to_string(), activity_type.to_string(), false);
+        block.end_time = end;
+        block
+    }
+
+    /// 9:00-10:00 in the editor, 10:00-10:20 in the browser, 10:20-11:00 in the terminal
+    fn day() -> Vec<TimeBlock> {
+        vec![
+            block(at(9, 0), at(10, 0), "editor", "productive"),
+            block(at(10, 0), at(10, 20), "browser", "distraction"),
+            block(at(10, 20), at(11, 0), "terminal", "productive"),
+        ]
+    }
+
+    fn spans(blocks: &[TimeBlock]) -> Vec<(DateTime<Local>, DateTime<Local>, &str)> {
+        blocks
+            .iter()
+            .map(|block| (block.start_time, block.end_time, block.application.as_str()))
+            .collect()
+    }
+
+    fn manual(start: DateTime<Local>, end: DateTime<Local>) -> Correction {
+        Correction::Manual {
+            start,
+            end,
+            label: "whiteboard".to_string(),
+            category: "meeting".to_string(),
+            project: None,
+            tags: vec!["offline".to_string()],
+            note: Some("Design session".to_string()),
+        }
+    }
+
+    #[test]
+    fn split_cuts_the_block_at_the_time() {
+        let blocks = apply(day(), &[Correction::Split { at: at(9, 30) }, Correction::Split { at: at(12, 0) }]);
+        assert_eq!(
+            spans(&blocks),
+            vec![
+                (at(9, 0), at(9, 30), "editor"),
+                (at(9, 30), at(10, 0), "editor"),
+                (at(10, 0), at(10, 20), "browser"),
+                (at(10, 20), at(11, 0), "terminal"),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_keeps_the_longest_block_and_every_tag_and_note() {
+        let blocks = apply(
+            day(),
+            &[
+                Correction::Tag { block_start: at(9, 0), tags: vec!["deep".to_string()] },
+                Correction::Note { block_start: at(10, 0), text: "docs".to_string() },
+                Correction::Merge { first_start: at(9, 0), last_start: at(10, 20) },
+            ],
+        );
+        assert_eq!(spans(&blocks), vec![(at(9, 0), at(11, 0), "editor")]);
+        assert_eq!(blocks[0].activity_type, "productive");
+        assert_eq!(blocks[0].tags, vec!["deep"]);
+        assert_eq!(blocks[0].notes, vec!["docs"]);
+    }
+
+    #[test]
+    fn manual_time_replaces_what_was_recorded() {
+        let blocks = apply(day(), &[manual(at(9, 45), at(10, 30))]);
+        assert_eq!(
+            spans(&blocks),
+            vec![
+                (at(9, 0), at(9, 45), "editor"),
+                (at(9, 45), at(10, 30), "whiteboard"),
+                (at(10, 30), at(11, 0), "terminal"),
+            ]
+        );
+        assert!(blocks[1].manual);
+        assert_eq!(blocks[1].activity_type, "meeting");
+        assert_eq!(blocks[1].notes, vec!["Design session"]);
+
+        // Inside a single block, the block is split around the manual time
+        let blocks = apply(day(), &[manual(at(9, 10), at(9, 20))]);
+        assert_eq!(
+            spans(&blocks)[..3],
+            [
+                (at(9, 0), at(9, 10), "editor"),
+                (at(9, 10), at(9, 20), "whiteboard"),
+                (at(9, 20), at(10, 0), "editor"),
+            ]
+        );
+    }
+
+    #[test]
+    fn undo_removes_the_last_correction_from_the_file() {
+        let dir = std::env::temp_dir().join(format!("timesense-corrections-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = Config {
+            data_directory: dir.to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        storage::save_blocks(&config.data_directory, date, &day()).unwrap();
+        let stored = || -> Vec<serde_json::Value> {
+            let contents = fs::read_to_string(corrections_path(&config.data_directory, date)).unwrap();
+            serde_json::from_str(&contents).unwrap()
+        };
+
+        run(&config, date, BlocksAction::Split { index: 1, at: time(9, 30) }).unwrap();
+        run(
+            &config,
+            date,
+            BlocksAction::Note {
+                index: 3,
+                text: "docs".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(stored().len(), 2);
+
+        run(&config, date, BlocksAction::Undo).unwrap();
+        let corrections = stored();
+        assert_eq!(corrections.len(), 1);
+        assert_eq!(corrections[0]["op"], "split");
+        let blocks = storage::load_effective_blocks(&config.data_directory, date).unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert!(blocks[2].notes.is_empty());
+
+        run(&config, date, BlocksAction::Undo).unwrap();
+        assert!(stored().is_empty());
+        // Nothing left to undo
+        run(&config, date, BlocksAction::Undo).unwrap();
+        assert!(stored().is_empty());
+        assert_eq!(storage::load_effective_blocks(&config.data_directory, date).unwrap().len(), 3);
+        assert!(!corrections_path(&config.data_directory, date).with_extension("json.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn times_before_the_day_start_belong_to_the_next_calendar_day() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let next = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap();
+
+        let late = local_time(date, time(1, 0), time(4, 0)).unwrap();
+        assert_eq!(late.naive_local(), next.and_time(time(1, 0)));
+        let morning = local_time(date, time(9, 0), time(4, 0)).unwrap();
+        assert_eq!(morning.naive_local(), date.and_time(time(9, 0)));
+        let midnight = local_time(date, time(0, 0), time(0, 0)).unwrap();
+        assert_eq!(midnight.naive_local(), date.and_time(time(0, 0)));
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use std::collections::BTreeMap;
+
+use crate::TimeBlock;
+
+/// The day a moment is attributed to. With a `day_start` of 04:00, anything
+/// before 4am still counts toward the previous day.
+pub fn logical_date(time: DateTime<Local>, day_start: NaiveTime) -> NaiveDate {
+    let offset = day_start.signed_duration_since(NaiveTime::MIN);
+    (time.naive_local() - offset).date()
+}
+
+/// The moment the logical day `date` begins
+pub fn start_of_day(date: NaiveDate, day_start: NaiveTime) -> DateTime<Local> {
+    start_of_day_in(&Local, date, day_start)
+}
+
+fn start_of_day_in<Tz: TimeZone>(zone: &Tz, date: NaiveDate, day_start: NaiveTime) -> DateTime<Tz> {
+    let naive = date.and_time(day_start);
+    zone.from_local_datetime(&naive)
+        .earliest()
+        // The boundary fell into a DST gap; the day starts once clocks resume
+        .or_else(|| zone.from_local_datetime(&(naive + Duration::hours(1))).earliest())
+        .expect("Day boundary does not exist in the local time zone")
+}
+
+/// Groups blocks by logical day, splitting those that run across a day
+/// boundary
+pub fn split_by_day(blocks: Vec<TimeBlock>, day_start: NaiveTime) -> BTreeMap<NaiveDate, Vec<TimeBlock>> {
+    let mut days: BTreeMap<NaiveDate, Vec<TimeBlock>> = BTreeMap::new();
+    for mut block in blocks {
+        loop {
+            let date = logical_date(block.start_time, day_start);
+            let boundary = start_of_day(date + Duration::days(1), day_start);
+            if block.end_time <= boundary {
+                days.entry(date).or_default().push(block);
+                break;
+            }
+            let mut first = block.clone();
+            first.end_time = boundary;
+            days.entry(date).or_default().push(first);
+            block.start_time = boundary;
+        }
+    }
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe::Berlin;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn early_hours_belong_to_the_previous_day() {
+        let four = time(4, 0);
+        assert_eq!(logical_date(at(20, 3, 59), four), date(10, 19));
+        assert_eq!(logical_date(at(20, 4, 0), four), date(10, 20));
+        assert_eq!(logical_date(at(20, 0, 0), NaiveTime::MIN), date(10, 20));
+        assert_eq!(start_of_day(date(10, 20), four), at(20, 4, 0));
+    }
+
+    #[test]
+    fn a_day_start_in_a_dst_gap_moves_to_when_clocks_resume() {
+        // Clocks in Berlin skip from 02:00 to 03:00 on 29 March 2026
+        let start = start_of_day_in(&Berlin, date(3, 29), time(2, 30));
+        assert_eq!(start.to_rfc3339(), "2026-03-29T03:30:00+02:00");
+
+        // On 25 October 02:30 happens twice; the day starts at the first
+        let start = start_of_day_in(&Berlin, date(10, 25), time(2, 30));
+        assert_eq!(start.to_rfc3339(), "2026-10-25T02:30:00+02:00");
+
+        let start = start_of_day_in(&Berlin, date(10, 26), time(2, 30));
+        assert_eq!(start.to_rfc3339(), "2026-10-26T02:30:00+01:00");
+    }
+
+    #[test]
+    fn blocks_are_split_at_the_day_start() {
+        let block = |start: DateTime<Local>, end: DateTime<Local>| {
+            let mut block = TimeBlock::new(start, "editor".to_string(), "productive".to_string(), false);
+            block.end_time = end;
+            block
+        };
+        let blocks = vec![
+            block(at(19, 22, 0), at(20, 3, 0)),
+            // Across two boundaries
+            block(at(20, 3, 0), at(21, 5, 0)),
+            // Ends exactly at the boundary
+            block(at(21, 5, 0), at(22, 4, 0)),
+        ];
+
+        let days = split_by_day(blocks, time(4, 0));
+        let spans: Vec<(NaiveDate, DateTime<Local>, DateTime<Local>)> = days
+            .iter()
+            .flat_map(|(date, blocks)| blocks.iter().map(move |block| (*date, block.start_time, block.end_time)))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (date(10, 19), at(19, 22, 0), at(20, 3, 0)),
+                (date(10, 19), at(20, 3, 0), at(20, 4, 0)),
+                (date(10, 20), at(20, 4, 0), at(21, 4, 0)),
+                (date(10, 21), at(21, 4, 0), at(21, 5, 0)),
+                (date(10, 21), at(21, 5, 0), at(22, 4, 0)),
+            ]
+        );
+    }
+}
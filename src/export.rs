@@ -0,0 +1,596 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::activitywatch;
+use crate::cli::{ExportFormat, Granularity};
+use crate::config::Config;
+use crate::ics;
+use crate::storage;
+use crate::time_format::{format_duration, percentage};
+use crate::timewarrior;
+use crate::{build_daily_summary, TimeBlock};
+
+/// One value of an exported table. Durations are kept as seconds for CSV and
+/// JSON and formatted for people in Markdown.
+pub enum Cell {
+    Text(String),
+    Seconds(i64),
+    Number(f64),
+    Bool(bool),
+    List(Vec<String>),
+    Empty,
+}
+
+/// Column names and rows, independent of the output format. Columns holding
+/// `Cell::Seconds` end in `_seconds`.
+pub struct Table {
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Cell {
+    fn optional(text: &Option<String>) -> Self {
+        match text {
+            Some(text) => Cell::Text(text.clone()),
+            None => Cell::Empty,
+        }
+    }
+
+    fn plain(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Seconds(seconds) => seconds.to_string(),
+            Cell::Number(number) => format!("{:.2}", number),
+            Cell::Bool(value) => value.to_string(),
+            Cell::List(items) => items.join(";"),
+            Cell::Empty => String::new(),
+        }
+    }
+
+    fn human(&self) -> String {
+        match self {
+            Cell::Seconds(seconds) => format_duration(Duration::seconds(*seconds)),
+            Cell::List(items) => items.join(", "),
+            other => other.plain(),
+        }
+    }
+
+    fn json(&self) -> Value {
+        match self {
+            Cell::Text(text) => json!(text),
+            Cell::Seconds(seconds) => json!(seconds),
+            Cell::Number(number) => json!(number),
+            Cell::Bool(value) => json!(value),
+            Cell::List(items) => json!(items),
+            Cell::Empty => Value::Null,
+        }
+    }
+}
+
+impl Table {
+    fn to_csv(&self) -> Result<String, String> {
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Cell::plain).collect())
+            .collect();
+        to_csv(&self.columns, &rows)
+    }
+
+    fn to_json_objects(&self) -> Vec<Value> {
+        self.rows
+            .iter()
+            .map(|row| {
+                let object: Map<String, Value> = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, cell)| (column.to_string(), cell.json()))
+                    .collect();
+                Value::Object(object)
+            })
+            .collect()
+    }
+
+    fn to_markdown(&self) -> String {
+        let headers: Vec<&str> = self
+            .columns
+            .iter()
+            .map(|column| column.strip_suffix("_seconds").unwrap_or(column))
+            .collect();
+        let mut md = format!("| {} |\n|{}\n", headers.join(" | "), "---|".repeat(headers.len()));
+        for row in &self.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|cell| cell.human().replace('|', "\\|").replace('\n', " "))
+                .collect();
+            md.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        md
+    }
+
+    pub fn render(&self, format: ExportFormat) -> Result<String, String> {
+        match format {
+            ExportFormat::Csv => self.to_csv(),
+            ExportFormat::Json => serde_json::to_string_pretty(&self.to_json_objects())
+                .map(|json| json + "\n")
+                .map_err(|e| e.to_string()),
+            ExportFormat::Jsonl => self
+                .to_json_objects()
+                .iter()
+                .map(|object| serde_json::to_string(object).map(|line| line + "\n"))
+                .collect::<Result<String, _>>()
+                .map_err(|e| e.to_string()),
+            ExportFormat::Markdown => Ok(self.to_markdown()),
+            ExportFormat::Ics | ExportFormat::ActivityWatch | ExportFormat::Timewarrior => {
+                Err("This format is only available for blocks".to_string())
+            }
+        }
+    }
+}
+
+fn duration_of(block: &TimeBlock) -> Duration {
+    block.end_time.signed_duration_since(block.start_time)
+}
+
+/// Category of a block for aggregation; idle time is its own category
+fn category_of(block: &TimeBlock) -> &str {
+    if block.idle {
+        "idle"
+    } else {
+        &block.activity_type
+    }
+}
+
+fn block_table(days: &[(NaiveDate, Vec<TimeBlock>)]) -> Table {
+    let rows = days
+        .iter()
+        .flat_map(|(date, blocks)| {
+            blocks.iter().map(move |block| {
+                vec![
+                    Cell::Text(date.to_string()),
+                    Cell::Text(block.start_time.to_rfc3339()),
+                    Cell::Text(block.end_time.to_rfc3339()),
+                    Cell::Seconds(duration_of(block).num_seconds()),
+                    Cell::Text(block.application.clone()),
+                    Cell::optional(&block.window_title),
+                    Cell::Text(block.activity_type.clone()),
+                    Cell::Bool(block.idle),
+                    Cell::optional(&block.project),
+                    Cell::optional(&block.branch),
+                    Cell::List(block.tags.clone()),
+                    Cell::List(block.notes.clone()),
+                    Cell::Bool(block.manual),
+                    Cell::optional(&block.meeting),
+                ]
+            })
+        })
+        .collect();
+
+    Table {
+        columns: vec![
+            "date",
+            "start",
+            "end",
+            "duration_seconds",
+            "application",
+            "window_title",
+            "category",
+            "idle",
+            "project",
+            "branch",
+            "tags",
+            "notes",
+            "manual",
+            "meeting",
+        ],
+        rows,
+    }
+}
+
+/// Total time per key over the whole range, longest first
+fn totals_table(days: &[(NaiveDate, Vec<TimeBlock>)], column: &'static str, key: fn(&TimeBlock) -> &str) -> Table {
+    let mut totals: HashMap<&str, Duration> = HashMap::new();
+    let mut tracked = Duration::zero();
+    for block in days.iter().flat_map(|(_, blocks)| blocks) {
+        *totals.entry(key(block)).or_insert(Duration::zero()) += duration_of(block);
+        tracked += duration_of(block);
+    }
+
+    let mut totals: Vec<(&str, Duration)> = totals.into_iter().collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    Table {
+        columns: vec![column, "duration_seconds", "share_percent"],
+        rows: totals
+            .into_iter()
+            .map(|(name, duration)| {
+                vec![
+                    Cell::Text(name.to_string()),
+                    Cell::Seconds(duration.num_seconds()),
+                    Cell::Number(percentage(duration, tracked)),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn day_table(config: &Config, days: &[(NaiveDate, Vec<TimeBlock>)]) -> Table {
+    let rows = days
+        .iter()
+        .map(|(date, blocks)| {
+            let summary = build_daily_summary(blocks, *date, config.focus_tolerance());
+            vec![
+                Cell::Text(date.to_string()),
+                Cell::Seconds(summary.tracked_time.num_seconds()),
+                Cell::Seconds(summary.productive_time.num_seconds()),
+                Cell::Seconds(summary.distracted_time.num_seconds()),
+                Cell::Seconds(summary.neutral_time.num_seconds()),
+                Cell::Seconds(summary.idle_time.num_seconds()),
+                match config.scoring.score_summary(&summary) {
+                    Some(score) => Cell::Number(score),
+                    None => Cell::Empty,
+                },
+                Cell::Number(summary.focus_metrics.switches_per_hour),
+            ]
+        })
+        .collect();
+
+    Table {
+        columns: vec![
+            "date",
+            "tracked_seconds",
+            "productive_seconds",
+            "distracted_seconds",
+            "neutral_seconds",
+            "idle_seconds",
+            "score",
+            "switches_per_hour",
+        ],
+        rows,
+    }
+}
+
+/// The blocks of `days` as JSON objects, as written by `--format json`
+pub fn block_objects(days: &[(NaiveDate, Vec<TimeBlock>)]) -> Vec<Value> {
+    block_table(days).to_json_objects()
+}
+
+/// One JSON summary object per day, as written by `--granularity day
+/// --format json`
+pub fn day_objects(config: &Config, days: &[(NaiveDate, Vec<TimeBlock>)]) -> Vec<Value> {
+    day_table(config, days).to_json_objects()
+}
+
+/// Which activity sessions an ICS export includes
+pub struct SessionFilter {
+    /// Shorter sessions are left out
+    pub min_length: Duration,
+    /// Categories to include; empty means every category
+    pub categories: Vec<String>,
+}
+
+/// A run of active blocks of one category, possibly across several apps
+struct Session {
+    category: String,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    /// Active time per application
+    apps: BTreeMap<String, Duration>,
+    notes: Vec<String>,
+}
+
+impl Session {
+    fn new(block: &TimeBlock) -> Self {
+        let mut session = Session {
+            category: block.activity_type.clone(),
+            start: block.start_time,
+            end: block.end_time,
+            apps: BTreeMap::new(),
+            notes: Vec::new(),
+        };
+        session.add(block);
+        session
+    }
+
+    fn add(&mut self, block: &TimeBlock) {
+        self.end = self.end.max(block.end_time);
+        *self.apps.entry(block.application.clone()).or_insert(Duration::zero()) += duration_of(block);
+        for note in &block.notes {
+            if !self.notes.contains(note) {
+                self.notes.push(note.clone());
+            }
+        }
+    }
+
+    /// Applications, longest first
+    fn apps_by_time(&self) -> Vec<(&String, &Duration)> {
+        let mut apps: Vec<(&String, &Duration)> = self.apps.iter().collect();
+        apps.sort_by(|a, b| b.1.cmp(a.1));
+        apps
+    }
+
+    /// E.g. "Deep work: code – 1h 40m"
+    fn title(&self) -> String {
+        let label = match self.category.as_str() {
+            "productive" => "Deep work".to_string(),
+            category => {
+                let mut chars = category.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+        };
+        let app = self.apps_by_time().first().map(|(app, _)| app.as_str()).unwrap_or("");
+        let length = self.end.signed_duration_since(self.start);
+        format!("{}: {} – {}", label, app, format_duration(length))
+    }
+
+    fn to_event(&self) -> ics::Event {
+        let mut description: Vec<String> = self
+            .apps_by_time()
+            .iter()
+            .map(|(app, time)| format!("{}: {}", app, format_duration(**time)))
+            .collect();
+        description.extend(self.notes.iter().cloned());
+        ics::Event {
+            uid: format!("{}-{}@timesense", self.start.timestamp(), self.category),
+            start: self.start,
+            end: self.end,
+            summary: self.title(),
+            description: Some(description.join("\n")),
+            categories: vec![self.category.clone()],
+        }
+    }
+}
+
+/// Merges each category's active blocks into sessions. Interruptions (other
+/// categories, idle time or gaps) up to `tolerance` don't end a session.
+fn sessions(days: &[(NaiveDate, Vec<TimeBlock>)], filter: &SessionFilter, tolerance: Duration) -> Vec<Session> {
+    let mut open: HashMap<String, Session> = HashMap::new();
+    let mut finished = Vec::new();
+
+    for block in days.iter().flat_map(|(_, blocks)| blocks) {
+        if block.idle {
+            continue;
+        }
+        if !filter.categories.is_empty() && !filter.categories.contains(&block.activity_type) {
+            continue;
+        }
+        match open.get_mut(&block.activity_type) {
+            Some(session) if block.start_time.signed_duration_since(session.end) <= tolerance => {
+                session.add(block);
+            }
+            _ => {
+                if let Some(session) = open.insert(block.activity_type.clone(), Session::new(block)) {
+                    finished.push(session);
+                }
+            }
+        }
+    }
+
+    finished.extend(open.into_values());
+    finished.retain(|session| session.end.signed_duration_since(session.start) >= filter.min_length);
+    finished.sort_by_key(|session| session.start);
+    finished
+}
+
+/// Writes a header and rows as CSV, quoting where needed
+pub fn to_csv(header: &[&str], rows: &[Vec<String>]) -> Result<String, String> {
+    let to_error = |e: csv::Error| format!("Failed to write CSV: {}", e);
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(header).map_err(to_error)?;
+    for row in rows {
+        writer.write_record(row).map_err(to_error)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| format!("Failed to write CSV: {}", e))?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// Loads the tracked days of the range, with corrections applied
+pub fn load_days(config: &Config, from: NaiveDate, to: NaiveDate) -> Result<Vec<(NaiveDate, Vec<TimeBlock>)>, String> {
+    if from > to {
+        return Err(format!("--from ({}) is after --to ({})", from, to));
+    }
+    let mut days = Vec::new();
+    for date in storage::dates_in_range(from, to) {
+        let blocks = storage::load_effective_blocks(&config.data_directory, date)?;
+        if !blocks.is_empty() {
+            days.push((date, blocks));
+        }
+    }
+    Ok(days)
+}
+
+/// Writes `content` to `output`, or to stdout without one
+pub fn write_output(content: &str, output: Option<&Path>) -> Result<(), String> {
+    match output {
+        Some(path) => {
+            fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            eprintln!("Wrote {}", path.display());
+            Ok(())
+        }
+        None => io::stdout()
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to write to stdout: {}", e)),
+    }
+}
+
+pub fn run(
+    config: &Config,
+    from: NaiveDate,
+    to: NaiveDate,
+    format: ExportFormat,
+    granularity: Granularity,
+    filter: &SessionFilter,
+    output: Option<&Path>,
+) -> Result<(), String> {
+    let days = load_days(config, from, to)?;
+    match format {
+        ExportFormat::Ics | ExportFormat::ActivityWatch | ExportFormat::Timewarrior
+            if !matches!(granularity, Granularity::Block) =>
+        {
+            return Err("--format ics, activitywatch and timewarrior export blocks; they can't be combined with --granularity".to_string());
+        }
+        ExportFormat::Ics => {
+            let events: Vec<ics::Event> = sessions(&days, filter, config.focus_tolerance())
+                .iter()
+                .map(Session::to_event)
+                .collect();
+            return write_output(&ics::write_calendar(&events), output);
+        }
+        ExportFormat::ActivityWatch => return write_output(&activitywatch::export(&days)?, output),
+        ExportFormat::Timewarrior => return write_output(&timewarrior::export(&days), output),
+        _ => {}
+    }
+    let table = match granularity {
+        Granularity::Block => block_table(&days),
+        Granularity::App => totals_table(&days, "application", |block| &block.application),
+        Granularity::Category => totals_table(&days, "category", category_of),
+        Granularity::Day => day_table(config, &days),
+    };
+    write_output(&table.render(format)?, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 19, hour, minute, 0).unwrap()
+    }
+
+    fn block(start: DateTime<Local>, end: DateTime<Local>, app: &str, category: &str, idle: bool) -> TimeBlock {
+        let mut block = TimeBlock::new(start, app.to_string(), category.to_string(), idle);
+        block.end_time = end;
+        block
+    }
+
+    fn day(blocks: Vec<TimeBlock>) -> Vec<(NaiveDate, Vec<TimeBlock>)> {
+        vec![(at(0, 0).date_naive(), blocks)]
+    }
+
+    fn spans(sessions: &[Session]) -> Vec<(&str, DateTime<Local>, DateTime<Local>)> {
+        sessions
+            .iter()
+            .map(|session| (session.category.as_str(), session.start, session.end))
+            .collect()
+    }
+
+    #[test]
+    fn sessions_end_at_idle_time_and_longer_category_changes() {
+        let days = day(vec![
+            block(at(9, 0), at(9, 30), "editor", "productive", false),
+            // Within the tolerance
+            block(at(9, 30), at(9, 33), "chat", "distraction", false),
+            block(at(9, 33), at(10, 0), "terminal", "productive", false),
+            // Past the tolerance
+            block(at(10, 0), at(10, 20), "chat", "distraction", false),
+            block(at(10, 20), at(10, 40), "editor", "productive", false),
+            block(at(10, 40), at(11, 0), "editor", "productive", true),
+            block(at(11, 0), at(11, 30), "editor", "productive", false),
+        ]);
+        let every = SessionFilter {
+            min_length: Duration::zero(),
+            categories: Vec::new(),
+        };
+        let sessions = sessions(&days, &every, Duration::minutes(5));
+        assert_eq!(
+            spans(&sessions),
+            vec![
+                ("productive", at(9, 0), at(10, 0)),
+                ("distraction", at(9, 30), at(9, 33)),
+                ("distraction", at(10, 0), at(10, 20)),
+                ("productive", at(10, 20), at(10, 40)),
+                ("productive", at(11, 0), at(11, 30)),
+            ]
+        );
+        assert_eq!(sessions[0].apps["terminal"], Duration::minutes(27));
+        assert_eq!(sessions[0].title(), "Deep work: editor – 1h");
+        assert_eq!(sessions[2].title(), "Distraction: chat – 20m");
+
+        let long_focus = SessionFilter {
+            min_length: Duration::minutes(25),
+            categories: vec!["productive".to_string()],
+        };
+        let sessions = super::sessions(&days, &long_focus, Duration::minutes(5));
+        assert_eq!(
+            spans(&sessions),
+            vec![("productive", at(9, 0), at(10, 0)), ("productive", at(11, 0), at(11, 30))]
+        );
+    }
+
+    fn sample_days() -> Vec<(NaiveDate, Vec<TimeBlock>)> {
+        let mut chat = block(at(9, 30), at(9, 45), "chat", "distraction", false);
+        chat.window_title = Some(r#"Re: "lunch" | today, 1pm"#.to_string());
+        day(vec![
+            block(at(9, 0), at(9, 30), "editor", "productive", false),
+            chat,
+            block(at(9, 45), at(10, 0), "editor", "productive", true),
+        ])
+    }
+
+    #[test]
+    fn tables_render_as_csv() {
+        let days = sample_days();
+        let totals = totals_table(&days, "category", category_of);
+        assert_eq!(
+            totals.render(ExportFormat::Csv).unwrap(),
+            "category,duration_seconds,share_percent\n\
+             productive,1800,50.00\n\
+             distraction,900,25.00\n\
+             idle,900,25.00\n"
+        );
+
+        let blocks = block_table(&days).render(ExportFormat::Csv).unwrap();
+        let chat_row = blocks.lines().nth(2).unwrap();
+        assert!(chat_row.contains(r#",900,chat,"Re: ""lunch"" | today, 1pm",distraction,false,"#));
+    }
+
+    #[test]
+    fn tables_render_as_json() {
+        let days = sample_days();
+        let totals = totals_table(&days, "category", category_of);
+        let json: Value = serde_json::from_str(&totals.render(ExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            json!([
+                { "category": "productive", "duration_seconds": 1800, "share_percent": 50.0 },
+                { "category": "distraction", "duration_seconds": 900, "share_percent": 25.0 },
+                { "category": "idle", "duration_seconds": 900, "share_percent": 25.0 },
+            ])
+        );
+
+        let lines = totals.render(ExportFormat::Jsonl).unwrap();
+        assert_eq!(lines.lines().count(), 3);
+
+        let blocks = block_objects(&days);
+        assert_eq!(blocks[1]["window_title"], r#"Re: "lunch" | today, 1pm"#);
+        assert_eq!(blocks[1]["project"], Value::Null);
+        assert_eq!(blocks[2]["idle"], true);
+    }
+
+    #[test]
+    fn tables_render_as_markdown() {
+        let days = sample_days();
+        let totals = totals_table(&days, "category", category_of);
+        assert_eq!(
+            totals.render(ExportFormat::Markdown).unwrap(),
+            "| category | duration | share_percent |\n\
+             |---|---|---|\n\
+             | productive | 30m | 50.00 |\n\
+             | distraction | 15m | 25.00 |\n\
+             | idle | 15m | 25.00 |\n"
+        );
+
+        let blocks = block_table(&days).render(ExportFormat::Markdown).unwrap();
+        assert!(blocks.contains(r#"| 15m | chat | Re: "lunch" \| today, 1pm | distraction |"#));
+        assert!(block_table(&days).render(ExportFormat::Ics).is_err());
+    }
+}
@@ -0,0 +1,464 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::corrections;
+use crate::day_boundary;
+use crate::storage;
+use crate::time_format::{format_duration, percentage};
+use crate::TimeBlock;
+
+/// How far back streaks are followed
+const STREAK_LOOKBACK_DAYS: i64 = 365;
+
+/// Goal states of a finished day, with the modification times of the day's
+/// blocks and corrections files they were computed from
+struct PastDay {
+    files: [Option<SystemTime>; 2],
+    /// `None` for a goal on a day without any tracking
+    states: Vec<(Goal, NaiveTime, Option<GoalState>)>,
+}
+
+/// Finished days by data directory and date, so streaks don't read every
+/// day file again until it changes
+static PAST_DAYS: Mutex<BTreeMap<(String, NaiveDate), PastDay>> = Mutex::new(BTreeMap::new());
+
+/// Forgets the remembered goal states, so past days are evaluated again at
+/// next use
+pub fn clear_cache() {
+    PAST_DAYS.lock().unwrap().clear();
+}
+
+/// A daily target, checked against the blocks of each logical day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Goal {
+    pub name: String,
+    pub kind: GoalKind,
+    /// Counts active blocks of this category
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Counts active blocks of applications whose name contains this
+    /// (case-insensitive); combined with `category` both must match
+    #[serde(default)]
+    pub app: Option<String>,
+    /// Target for `at_least` and budget for `at_most`
+    #[serde(default)]
+    pub minutes: Option<u64>,
+    /// Earliest allowed start for `not_before`
+    #[serde(default)]
+    pub time: Option<NaiveTime>,
+    /// Weekdays the goal applies to; empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalKind {
+    /// At least `minutes` of matching time
+    AtLeast,
+    /// At most `minutes` of matching time
+    AtMost,
+    /// No matching activity before `time`
+    NotBefore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalState {
+    Met,
+    /// Not decided yet because the day is still running
+    InProgress,
+    Missed,
+}
+
+impl GoalState {
+    pub fn label(self) -> &'static str {
+        match self {
+            GoalState::Met => "met",
+            GoalState::InProgress => "in progress",
+            GoalState::Missed => "missed",
+        }
+    }
+
+    pub fn css_class(self) -> &'static str {
+        match self {
+            GoalState::Met => "goal-met",
+            GoalState::InProgress => "goal-in-progress",
+            GoalState::Missed => "goal-missed",
+        }
+    }
+}
+
+/// Where a goal stands on one day
+#[derive(Debug, Clone)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub state: GoalState,
+    /// Matching time so far
+    pub actual: Duration,
+    /// First matching activity before the allowed time, for `not_before`
+    pub violation: Option<DateTime<Local>>,
+    /// Consecutive days the goal was met, up to and including this day
+    /// (or up to the day before while this one is in progress)
+    pub streak: usize,
+}
+
+impl GoalProgress {
+    /// How far along the goal is, in percent; budgets go past 100 when exceeded
+    pub fn percent(&self) -> f64 {
+        match self.goal.target() {
+            Some(target) => percentage(self.actual, target),
+            None if self.state == GoalState::Missed => 0.0,
+            None => 100.0,
+        }
+    }
+
+    /// "2h 10m of 4h", "35m of 30m" or "first at 09:12"
+    pub fn amount(&self) -> String {
+        match (self.goal.target(), self.violation) {
+            (Some(target), _) => format!("{} of {}", format_duration(self.actual), format_duration(target)),
+            (None, Some(violation)) => format!("first at {}", violation.format("%H:%M")),
+            (None, None) if self.state == GoalState::InProgress => "none so far".to_string(),
+            (None, None) => "none".to_string(),
+        }
+    }
+
+    pub fn streak_text(&self) -> String {
+        match self.streak {
+            0 => "no streak".to_string(),
+            1 => "1-day streak".to_string(),
+            days => format!("{}-day streak", days),
+        }
+    }
+}
+
+impl Goal {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("a goal has an empty 'name'".to_string());
+        }
+        if self.category.is_none() && self.app.is_none() {
+            return Err(format!("goal '{}' needs a 'category' or an 'app'", self.name));
+        }
+        match self.kind {
+            GoalKind::AtLeast | GoalKind::AtMost if self.minutes.is_none() => {
+                Err(format!("goal '{}' needs 'minutes'", self.name))
+            }
+            GoalKind::NotBefore if self.time.is_none() => {
+                Err(format!("goal '{}' needs a 'time'", self.name))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn applies_on(&self, date: NaiveDate) -> bool {
+        self.days.is_empty() || self.days.contains(&date.weekday())
+    }
+
+    fn target(&self) -> Option<Duration> {
+        match self.kind {
+            GoalKind::AtLeast | GoalKind::AtMost => {
+                self.minutes.map(|minutes| Duration::minutes(minutes as i64))
+            }
+            GoalKind::NotBefore => None,
+        }
+    }
+
+    fn matches(&self, block: &TimeBlock) -> bool {
+        if block.idle {
+            return false;
+        }
+        if let Some(category) = &self.category {
+            if block.activity_type != *category {
+                return false;
+            }
+        }
+        match &self.app {
+            Some(app) => block.application.to_lowercase().contains(&app.to_lowercase()),
+            None => true,
+        }
+    }
+
+    /// E.g. "at least 4h of productive on Mon, Tue, Wed, Thu, Fri"
+    pub fn describe(&self) -> String {
+        let subject = match (&self.app, &self.category) {
+            (Some(app), Some(category)) => format!("{} ({})", app, category),
+            (Some(app), None) => app.clone(),
+            (None, Some(category)) => category.clone(),
+            (None, None) => "anything".to_string(),
+        };
+        let target = self.target().map(format_duration).unwrap_or_default();
+        let mut description = match self.kind {
+            GoalKind::AtLeast => format!("at least {} of {}", target, subject),
+            GoalKind::AtMost => format!("at most {} of {}", target, subject),
+            GoalKind::NotBefore => format!(
+                "no {} before {}",
+                subject,
+                self.time.unwrap_or(NaiveTime::MIN).format("%H:%M")
+            ),
+        };
+        if !self.days.is_empty() {
+            let days: Vec<String> = self.days.iter().map(|day| day.to_string()).collect();
+            description.push_str(&format!(" on {}", days.join(", ")));
+        }
+        description
+    }
+
+    /// Checks the goal against one day's blocks. The day counts as finished
+    /// once `now` is past its end.
+    pub fn evaluate(&self, blocks: &[TimeBlock], date: NaiveDate, day_start: NaiveTime, now: DateTime<Local>) -> (GoalState, Duration, Option<DateTime<Local>>) {
+        let day_over = now >= day_boundary::start_of_day(date + Duration::days(1), day_start);
+        let matching = || blocks.iter().filter(|block| self.matches(block));
+        let actual = matching().fold(Duration::zero(), |total, block| {
+            total + block.end_time.signed_duration_since(block.start_time)
+        });
+
+        match self.kind {
+            GoalKind::AtLeast => {
+                let state = if actual >= self.target().unwrap_or(Duration::zero()) {
+                    GoalState::Met
+                } else if day_over {
+                    GoalState::Missed
+                } else {
+                    GoalState::InProgress
+                };
+                (state, actual, None)
+            }
+            GoalKind::AtMost => {
+                let state = if actual > self.target().unwrap_or(Duration::zero()) {
+                    GoalState::Missed
+                } else if day_over {
+                    GoalState::Met
+                } else {
+                    GoalState::InProgress
+                };
+                (state, actual, None)
+            }
+            GoalKind::NotBefore => {
+                let deadline = self.deadline(date, day_start);
+                let violation = matching()
+                    .map(|block| block.start_time)
+                    .filter(|start| *start < deadline)
+                    .min();
+                let state = if violation.is_some() {
+                    GoalState::Missed
+                } else if now >= deadline || day_over {
+                    GoalState::Met
+                } else {
+                    GoalState::InProgress
+                };
+                (state, actual, violation)
+            }
+        }
+    }
+
+    /// The moment `time` occurs within the logical day `date`; times earlier
+    /// than `day_start` fall on the next calendar day
+    fn deadline(&self, date: NaiveDate, day_start: NaiveTime) -> DateTime<Local> {
+        let time = self.time.unwrap_or(day_start);
+        let mut offset = time.signed_duration_since(day_start);
+        if offset < Duration::zero() {
+            offset += Duration::days(1);
+        }
+        day_boundary::start_of_day(date, day_start) + offset
+    }
+}
+
+/// Progress on every goal that applies on `date`, given that day's blocks
+pub fn progress(config: &Config, date: NaiveDate, blocks: &[TimeBlock], now: DateTime<Local>) -> Result<Vec<GoalProgress>, String> {
+    let mut progress = Vec::new();
+    for goal in config.goals.iter().filter(|goal| goal.applies_on(date)) {
+        let (state, actual, violation) = goal.evaluate(blocks, date, config.day_start, now);
+        let streak = match state {
+            GoalState::Missed => 0,
+            GoalState::Met => 1 + streak_before(config, goal, date, now)?,
+            GoalState::InProgress => streak_before(config, goal, date, now)?,
+        };
+        progress.push(GoalProgress {
+            goal: goal.clone(),
+            state,
+            actual,
+            violation,
+            streak,
+        });
+    }
+    Ok(progress)
+}
+
+/// Number of consecutive days before `date` on which the goal was met.
+/// Days the goal doesn't apply to and days without any tracking are skipped.
+pub fn streak_before(config: &Config, goal: &Goal, date: NaiveDate, now: DateTime<Local>) -> Result<usize, String> {
+    let mut streak = 0;
+    for days_back in 1..=STREAK_LOOKBACK_DAYS {
+        let day = date - Duration::days(days_back);
+        if !goal.applies_on(day) {
+            continue;
+        }
+        match state_on(config, goal, day, now)? {
+            None | Some(GoalState::InProgress) => continue,
+            Some(GoalState::Met) => streak += 1,
+            Some(GoalState::Missed) => break,
+        }
+    }
+    Ok(streak)
+}
+
+/// The goal's state on `day`, or `None` if nothing was tracked. Finished
+/// days are remembered until their blocks or corrections change.
+fn state_on(config: &Config, goal: &Goal, day: NaiveDate, now: DateTime<Local>) -> Result<Option<GoalState>, String> {
+    let evaluate = || -> Result<Option<GoalState>, String> {
+        let blocks = storage::load_effective_blocks(&config.data_directory, day)?;
+        Ok((!blocks.is_empty()).then(|| goal.evaluate(&blocks, day, config.day_start, now).0))
+    };
+    if now < day_boundary::start_of_day(day + Duration::days(1), config.day_start) {
+        return evaluate();
+    }
+
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let files = [
+        modified(&storage::blocks_path(&config.data_directory, day)),
+        modified(&corrections::corrections_path(&config.data_directory, day)),
+    ];
+    let key = (config.data_directory.clone(), day);
+    {
+        let past_days = PAST_DAYS.lock().unwrap();
+        if let Some(past_day) = past_days.get(&key).filter(|past_day| past_day.files == files) {
+            let known = past_day
+                .states
+                .iter()
+                .find(|(known, day_start, _)| known == goal && *day_start == config.day_start);
+            if let Some((_, _, state)) = known {
+                return Ok(*state);
+            }
+        }
+    }
+
+    let state = evaluate()?;
+    let mut past_days = PAST_DAYS.lock().unwrap();
+    // Days beyond the streak lookback are never asked for again
+    let oldest = now.date_naive() - Duration::days(STREAK_LOOKBACK_DAYS + 1);
+    past_days.retain(|(_, date), _| *date >= oldest);
+    let past_day = past_days.entry(key).or_insert_with(|| PastDay {
+        files,
+        states: Vec::new(),
+    });
+    if past_day.files != files {
+        *past_day = PastDay { files, states: Vec::new() };
+    }
+    past_day.states.push((goal.clone(), config.day_start, state));
+    Ok(state)
+}
+
+/// How often each goal was met across several days: (goal, days met, days
+/// the goal applied and something was tracked)
+pub fn summarize<'a>(config: &'a Config, days: &[(NaiveDate, &[TimeBlock])], now: DateTime<Local>) -> Vec<(&'a Goal, usize, usize)> {
+    config
+        .goals
+        .iter()
+        .map(|goal| {
+            let applicable: Vec<GoalState> = days
+                .iter()
+                .filter(|(date, blocks)| goal.applies_on(*date) && !blocks.is_empty())
+                .map(|(date, blocks)| goal.evaluate(blocks, *date, config.day_start, now).0)
+                .collect();
+            let met = applicable.iter().filter(|state| **state == GoalState::Met).count();
+            (goal, met, applicable.len())
+        })
+        .collect()
+}
+
+/// Prints where each goal stands on `date`
+pub fn run(config: &Config, date: Option<NaiveDate>) -> Result<(), String> {
+    let now = Local::now();
+    let date = date.unwrap_or_else(|| day_boundary::logical_date(now, config.day_start));
+
+    if config.goals.is_empty() {
+        println!("No goals configured. Add a 'goals' list to the config file.");
+        return Ok(());
+    }
+
+    let blocks = storage::load_effective_blocks(&config.data_directory, date)?;
+    let progress = progress(config, date, &blocks, now)?;
+    if progress.is_empty() {
+        println!("No goals apply on {}.", date);
+        return Ok(());
+    }
+
+    println!("Goals for {}:", date);
+    for item in &progress {
+        let marker = match item.state {
+            GoalState::Met => "[x]",
+            GoalState::InProgress => "[ ]",
+            GoalState::Missed => "[!]",
+        };
+        println!(
+            "  {} {}: {} ({}) - {}, {}",
+            marker,
+            item.goal.name,
+            item.amount(),
+            item.goal.describe(),
+            item.state.label(),
+            item.streak_text()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::fs::File;
+
+    /// A day with `minutes` of productive time from 9:00
+    fn save_day(data_directory: &str, date: NaiveDate, minutes: i64) {
+        let start = Local.from_local_datetime(&date.and_hms_opt(9, 0, 0).unwrap()).unwrap();
+        let mut block = TimeBlock::new(start, "editor".to_string(), "productive".to_string(), false);
+        block.end_time = start + Duration::minutes(minutes);
+        storage::save_blocks(data_directory, date, &[block]).unwrap();
+    }
+
+    #[test]
+    fn streaks_follow_changes_to_past_days() {
+        let dir = std::env::temp_dir().join(format!("timesense-goals-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config = Config {
+            data_directory: dir.to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+        let goal = Goal {
+            name: "Deep work".to_string(),
+            kind: GoalKind::AtLeast,
+            category: Some("productive".to_string()),
+            app: None,
+            minutes: Some(60),
+            time: None,
+            days: Vec::new(),
+        };
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let day = |days_back: i64| date - Duration::days(days_back);
+        let now = Local.from_local_datetime(&date.and_hms_opt(12, 0, 0).unwrap()).unwrap();
+
+        save_day(&config.data_directory, day(1), 90);
+        save_day(&config.data_directory, day(2), 60);
+        // Nothing tracked on day(3)
+        save_day(&config.data_directory, day(4), 120);
+        save_day(&config.data_directory, day(5), 10);
+        assert_eq!(streak_before(&config, &goal, date, now), Ok(3));
+        assert_eq!(streak_before(&config, &goal, date, now), Ok(3));
+
+        // A changed day is read again
+        save_day(&config.data_directory, day(2), 30);
+        let path = storage::blocks_path(&config.data_directory, day(2));
+        let later = SystemTime::now() + std::time::Duration::from_secs(60);
+        File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(streak_before(&config, &goal, date, now), Ok(1));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
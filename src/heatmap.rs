@@ -0,0 +1,291 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike, Weekday};
+use serde::Serialize;
+
+use crate::templates::escape;
+use crate::time_format::{format_duration, percentage};
+use crate::timeline::{category_color, start_of_hour};
+use crate::TimeBlock;
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+const LABEL_WIDTH: f64 = 40.0;
+const AXIS_HEIGHT: f64 = 16.0;
+const CELL_SIZE: f64 = 36.0;
+const CELL_GAP: f64 = 2.0;
+
+/// Hour-of-day by weekday grid over a date range. Each cell holds the share
+/// of the time tracked in that hour slot that matched the chosen category.
+#[derive(Debug, Serialize)]
+pub struct Heatmap {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// What `share` measures: "productive" by default, or the chosen category
+    pub category: String,
+    /// Seven rows (Monday first) of 24 hourly cells
+    pub rows: Vec<HeatmapRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeatmapRow {
+    pub weekday: String,
+    pub cells: Vec<HeatmapCell>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeatmapCell {
+    pub hour: u32,
+    pub tracked_seconds: i64,
+    pub matching_seconds: i64,
+    /// Percentage of tracked time that matched, or null if nothing was tracked
+    pub share: Option<f64>,
+}
+
+impl Heatmap {
+    /// Builds the grid from blocks in local clock time. `category` defaults to
+    /// "productive"; "idle" matches idle blocks, any other category matches
+    /// non-idle blocks of that category.
+    pub fn build<'a>(
+        from: NaiveDate,
+        to: NaiveDate,
+        blocks: impl IntoIterator<Item = &'a TimeBlock>,
+        category: Option<&str>,
+    ) -> Self {
+        let category = category.unwrap_or("productive").to_string();
+        let mut tracked = [[Duration::zero(); 24]; 7];
+        let mut matching = [[Duration::zero(); 24]; 7];
+
+        for block in blocks {
+            let matches = if category == "idle" {
+                block.idle
+            } else {
+                !block.idle && block.activity_type == category
+            };
+
+            // Walk the block one clock hour at a time
+            let mut cursor = block.start_time;
+            while cursor < block.end_time {
+                let slot_end = next_hour(cursor).min(block.end_time);
+                let duration = slot_end.signed_duration_since(cursor);
+                let row = cursor.weekday().num_days_from_monday() as usize;
+                let hour = cursor.hour() as usize;
+
+                tracked[row][hour] += duration;
+                if matches {
+                    matching[row][hour] += duration;
+                }
+                cursor = slot_end;
+            }
+        }
+
+        let rows = WEEKDAYS
+            .iter()
+            .enumerate()
+            .map(|(row, weekday)| HeatmapRow {
+                weekday: weekday.to_string(),
+                cells: (0..24)
+                    .map(|hour| {
+                        let tracked = tracked[row][hour];
+                        let matching = matching[row][hour];
+                        HeatmapCell {
+                            hour: hour as u32,
+                            tracked_seconds: tracked.num_seconds(),
+                            matching_seconds: matching.num_seconds(),
+                            share: if tracked > Duration::zero() {
+                                Some(percentage(matching, tracked))
+                            } else {
+                                None
+                            },
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Heatmap {
+            from,
+            to,
+            category,
+            rows,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Renders the grid as inline SVG; cell opacity follows the share and
+    /// empty slots stay grey
+    pub fn render_svg(&self) -> String {
+        let width = LABEL_WIDTH + 24.0 * CELL_SIZE;
+        let height = AXIS_HEIGHT + 7.0 * CELL_SIZE;
+        let color = category_color(&self.category, self.category == "idle");
+
+        let mut svg = format!(
+            r#"<svg class="heatmap" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" width="100%" role="img" aria-label="Heatmap by weekday and hour">"#,
+            width, height
+        );
+        for hour in (0..24).step_by(3) {
+            svg.push_str(&format!(
+                r#"<text x="{:.1}" y="12" class="axis">{:02}:00</text>"#,
+                LABEL_WIDTH + hour as f64 * CELL_SIZE,
+                hour
+            ));
+        }
+
+        for (row_index, row) in self.rows.iter().enumerate() {
+            let y = AXIS_HEIGHT + row_index as f64 * CELL_SIZE;
+            svg.push_str(&format!(
+                r#"<text x="0" y="{:.1}" class="weekday">{}</text>"#,
+                y + CELL_SIZE * 0.6,
+                row.weekday
+            ));
+
+            for cell in &row.cells {
+                let x = LABEL_WIDTH + cell.hour as f64 * CELL_SIZE;
+                let (fill, opacity, tooltip) = match cell.share {
+                    Some(share) => (
+                        color,
+                        0.1 + 0.9 * share / 100.0,
+                        format!(
+                            "{} {:02}:00: {:.0}% {} ({} tracked)",
+                            row.weekday,
+                            cell.hour,
+                            share,
+                            escape(&self.category),
+                            format_duration(Duration::seconds(cell.tracked_seconds))
+                        ),
+                    ),
+                    None => (
+                        "#f0f0f0",
+                        1.0,
+                        format!("{} {:02}:00: nothing tracked", row.weekday, cell.hour),
+                    ),
+                };
+                svg.push_str(&format!(
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" fill-opacity="{:.2}"><title>{}</title></rect>"#,
+                    x,
+                    y,
+                    CELL_SIZE - CELL_GAP,
+                    CELL_SIZE - CELL_GAP,
+                    fill,
+                    opacity,
+                    tooltip
+                ));
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Markdown table with one column per hour; empty slots are blank
+    pub fn render_markdown(&self) -> String {
+        let mut md = String::from("| |");
+        for hour in 0..24 {
+            md.push_str(&format!(" {:02} |", hour));
+        }
+        md.push_str("\n|---|");
+        md.push_str(&"---|".repeat(24));
+        md.push('\n');
+
+        for row in &self.rows {
+            md.push_str(&format!("| {} |", row.weekday));
+            for cell in &row.cells {
+                match cell.share {
+                    Some(share) => md.push_str(&format!(" {:.0} |", share)),
+                    None => md.push_str("  |"),
+                }
+            }
+            md.push('\n');
+        }
+        md
+    }
+}
+
+fn next_hour(time: DateTime<Local>) -> DateTime<Local> {
+    start_of_hour(time) + Duration::hours(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// October 2026; the 19th is a Monday
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, day, hour, minute, 0).unwrap()
+    }
+
+    fn block(start: DateTime<Local>, end: DateTime<Local>, category: &str, idle: bool) -> TimeBlock {
+        let mut block = TimeBlock::new(start, "app".to_string(), category.to_string(), idle);
+        block.end_time = end;
+        block
+    }
+
+    fn cell(heatmap: &Heatmap, weekday: Weekday, hour: usize) -> &HeatmapCell {
+        &heatmap.rows[weekday.num_days_from_monday() as usize].cells[hour]
+    }
+
+    fn blocks() -> Vec<TimeBlock> {
+        vec![
+            // Across an hour boundary
+            block(at(19, 9, 40), at(19, 10, 20), "productive", false),
+            // Across midnight from Sunday into Monday
+            block(at(18, 23, 30), at(19, 0, 30), "productive", false),
+            block(at(21, 9, 0), at(21, 9, 30), "distraction", false),
+            block(at(21, 9, 30), at(21, 10, 0), "productive", true),
+            // The next Monday lands in the same row
+            block(at(26, 9, 0), at(26, 9, 20), "distraction", false),
+        ]
+    }
+
+    #[test]
+    fn time_is_bucketed_by_weekday_and_clock_hour() {
+        let blocks = blocks();
+        let heatmap = Heatmap::build(at(18, 0, 0).date_naive(), at(26, 0, 0).date_naive(), &blocks, None);
+        assert_eq!(heatmap.rows.len(), 7);
+        assert_eq!(heatmap.rows[0].weekday, "Mon");
+
+        let monday_nine = cell(&heatmap, Weekday::Mon, 9);
+        assert_eq!((monday_nine.tracked_seconds, monday_nine.matching_seconds), (40 * 60, 20 * 60));
+        assert_eq!(monday_nine.share, Some(50.0));
+        let monday_ten = cell(&heatmap, Weekday::Mon, 10);
+        assert_eq!((monday_ten.tracked_seconds, monday_ten.share), (20 * 60, Some(100.0)));
+
+        assert_eq!(cell(&heatmap, Weekday::Sun, 23).matching_seconds, 30 * 60);
+        assert_eq!(cell(&heatmap, Weekday::Mon, 0).matching_seconds, 30 * 60);
+
+        let wednesday_nine = cell(&heatmap, Weekday::Wed, 9);
+        assert_eq!(wednesday_nine.tracked_seconds, 60 * 60);
+        assert_eq!(wednesday_nine.share, Some(0.0));
+
+        // Nothing tracked is no share rather than zero
+        assert_eq!(cell(&heatmap, Weekday::Tue, 9).share, None);
+        let total: i64 = heatmap.rows.iter().flat_map(|row| &row.cells).map(|cell| cell.tracked_seconds).sum();
+        assert_eq!(total, (40 + 60 + 60 + 20) * 60);
+    }
+
+    #[test]
+    fn idle_and_other_categories_can_be_mapped() {
+        let blocks = blocks();
+        let from = at(18, 0, 0).date_naive();
+        let to = at(26, 0, 0).date_naive();
+
+        let idle = Heatmap::build(from, to, &blocks, Some("idle"));
+        assert_eq!(cell(&idle, Weekday::Wed, 9).share, Some(50.0));
+        assert_eq!(cell(&idle, Weekday::Mon, 9).share, Some(0.0));
+
+        let distraction = Heatmap::build(from, to, &blocks, Some("distraction"));
+        assert_eq!(distraction.category, "distraction");
+        assert_eq!(cell(&distraction, Weekday::Mon, 9).matching_seconds, 20 * 60);
+        assert_eq!(cell(&distraction, Weekday::Wed, 9).share, Some(50.0));
+    }
+}
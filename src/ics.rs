@@ -0,0 +1,983 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+/// Longest content line allowed by RFC 5545, in octets; longer lines are folded
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Windows time zone names, as Outlook and Exchange write them in TZID, and
+/// their IANA equivalents
+const WINDOWS_ZONES: [(&str, &str); 32] = [
+    ("Dateline Standard Time", "Etc/GMT+12"),
+    ("Hawaiian Standard Time", "Pacific/Honolulu"),
+    ("Alaskan Standard Time", "America/Anchorage"),
+    ("Pacific Standard Time", "America/Los_Angeles"),
+    ("US Mountain Standard Time", "America/Phoenix"),
+    ("Mountain Standard Time", "America/Denver"),
+    ("Central Standard Time", "America/Chicago"),
+    ("Eastern Standard Time", "America/New_York"),
+    ("Atlantic Standard Time", "America/Halifax"),
+    ("Newfoundland Standard Time", "America/St_Johns"),
+    ("E. South America Standard Time", "America/Sao_Paulo"),
+    ("Argentina Standard Time", "America/Argentina/Buenos_Aires"),
+    ("UTC", "Etc/UTC"),
+    ("GMT Standard Time", "Europe/London"),
+    ("W. Europe Standard Time", "Europe/Berlin"),
+    ("Romance Standard Time", "Europe/Paris"),
+    ("Central Europe Standard Time", "Europe/Budapest"),
+    ("Central European Standard Time", "Europe/Warsaw"),
+    ("E. Europe Standard Time", "Europe/Chisinau"),
+    ("FLE Standard Time", "Europe/Kyiv"),
+    ("GTB Standard Time", "Europe/Bucharest"),
+    ("Russian Standard Time", "Europe/Moscow"),
+    ("Israel Standard Time", "Asia/Jerusalem"),
+    ("Arabian Standard Time", "Asia/Dubai"),
+    ("India Standard Time", "Asia/Kolkata"),
+    ("China Standard Time", "Asia/Shanghai"),
+    ("Singapore Standard Time", "Asia/Singapore"),
+    ("Tokyo Standard Time", "Asia/Tokyo"),
+    ("Korea Standard Time", "Asia/Seoul"),
+    ("AUS Eastern Standard Time", "Australia/Sydney"),
+    ("New Zealand Standard Time", "Pacific/Auckland"),
+    ("South Africa Standard Time", "Africa/Johannesburg"),
+];
+
+/// A calendar event as written to or read from an iCalendar file
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub uid: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub summary: String,
+    pub description: Option<String>,
+    pub categories: Vec<String>,
+}
+
+/// Writes events as an iCalendar (RFC 5545) document. Times are written in UTC
+/// and the events are marked free, so they don't block time when overlaid on
+/// a calendar.
+pub fn write_calendar(events: &[Event]) -> String {
+    let stamp = format_time(Utc::now());
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//TimeSense//Activity export//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", escape(&event.uid)));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", format_time(event.start.with_timezone(&Utc))));
+        lines.push(format!("DTEND:{}", format_time(event.end.with_timezone(&Utc))));
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        if !event.categories.is_empty() {
+            let categories: Vec<String> = event.categories.iter().map(|category| escape(category)).collect();
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into 75-octet pieces joined by CRLF and a space,
+/// without cutting a UTF-8 character apart
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts toward the next line
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+/// How a recurring event repeats. Only daily and weekly rules are expanded;
+/// other events are read as single occurrences.
+#[derive(Debug, Clone)]
+pub struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<DateTime<Local>>,
+    /// Weekdays of a weekly rule; empty means the weekday of the first one
+    by_day: Vec<Weekday>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+}
+
+/// The time zone a calendar time is written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    /// No time zone: local time wherever the calendar is read
+    Floating,
+    Named(Tz),
+}
+
+impl Zone {
+    /// The moment a wall-clock time in this zone stands for; `None` for a
+    /// time skipped by a daylight saving change
+    fn resolve(self, time: NaiveDateTime) -> Option<DateTime<Local>> {
+        match self {
+            Zone::Floating => Local.from_local_datetime(&time).earliest(),
+            Zone::Named(zone) => zone
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|time| time.with_timezone(&Local)),
+        }
+    }
+
+    /// The wall-clock time of `time` in this zone
+    fn wall_clock(self, time: DateTime<Local>) -> NaiveDateTime {
+        match self {
+            Zone::Floating => time.naive_local(),
+            Zone::Named(zone) => time.with_timezone(&zone).naive_local(),
+        }
+    }
+}
+
+/// An event read from a calendar file, before recurrences are expanded
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    pub event: Event,
+    /// Time zone of the start, in which recurrences repeat
+    zone: Zone,
+    pub recurrence: Option<Recurrence>,
+    /// Start times of occurrences that were cancelled or moved
+    pub exceptions: Vec<DateTime<Local>>,
+    /// For a moved occurrence, the start time it replaces
+    recurrence_id: Option<DateTime<Local>>,
+}
+
+/// Longest expansion of a recurring event, as a guard against endless rules
+const MAX_OCCURRENCES: usize = 10_000;
+
+impl CalendarEvent {
+    /// Occurrences overlapping `from`..`to`
+    pub fn occurrences(&self, from: DateTime<Local>, to: DateTime<Local>) -> Vec<Event> {
+        let overlaps = |event: &Event| event.start < to && event.end > from;
+        let Some(rule) = &self.recurrence else {
+            return if overlaps(&self.event) { vec![self.event.clone()] } else { Vec::new() };
+        };
+
+        let length = self.event.end.signed_duration_since(self.event.start);
+        let first = self.zone.wall_clock(self.event.start);
+        let mut occurrences = Vec::new();
+        for (index, start) in rule.starts(first).take(MAX_OCCURRENCES).enumerate() {
+            // Times are repeated on the wall clock of the event's time zone,
+            // so daylight saving time doesn't shift them
+            let Some(start) = self.zone.resolve(start) else {
+                continue;
+            };
+            let past_count = matches!(rule.count, Some(count) if index as u32 >= count);
+            let past_until = matches!(rule.until, Some(until) if start > until);
+            if past_count || past_until || start >= to {
+                break;
+            }
+            if self.exceptions.contains(&start) {
+                continue;
+            }
+            let Some(end) = start.checked_add_signed(length) else {
+                break;
+            };
+            let occurrence = Event {
+                start,
+                end,
+                ..self.event.clone()
+            };
+            if overlaps(&occurrence) {
+                occurrences.push(occurrence);
+            }
+        }
+        occurrences
+    }
+}
+
+impl Recurrence {
+    /// Start times of every occurrence, beginning with `first`. The
+    /// iterator ends where the times leave the range chrono can represent.
+    fn starts(&self, first: NaiveDateTime) -> Box<dyn Iterator<Item = NaiveDateTime> + '_> {
+        let interval = self.interval.max(1) as i64;
+        match self.frequency {
+            Frequency::Daily => Box::new((0..).map_while(move |n: i64| {
+                n.checked_mul(interval)
+                    .and_then(Duration::try_days)
+                    .and_then(|step| first.checked_add_signed(step))
+            })),
+            Frequency::Weekly => {
+                let mut weekdays: Vec<Weekday> = if self.by_day.is_empty() {
+                    vec![first.weekday()]
+                } else {
+                    self.by_day.clone()
+                };
+                weekdays.sort_by_key(|day| day.num_days_from_monday());
+                let week_start = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+                Box::new(
+                    (0..)
+                        .map_while(move |week: i64| {
+                            week.checked_mul(interval)
+                                .and_then(Duration::try_weeks)
+                                .and_then(|step| week_start.checked_add_signed(step))
+                        })
+                        .flat_map(move |monday| {
+                            weekdays.clone().into_iter().map_while(move |day| {
+                                monday.checked_add_signed(Duration::days(day.num_days_from_monday() as i64))
+                            })
+                        })
+                        .filter(move |start| *start >= first),
+                )
+            }
+        }
+    }
+}
+
+/// Reads the timed events of an iCalendar document. All-day, cancelled and
+/// free (transparent) events are skipped, since they don't take up time.
+/// Times are read in their TZID (an IANA or common Windows time zone name),
+/// or as local time without one. Events that can't be read, including those
+/// in an unknown time zone, are left out with a warning for each.
+pub fn parse_calendar(text: &str) -> (Vec<CalendarEvent>, Vec<String>) {
+    let mut events: Vec<CalendarEvent> = Vec::new();
+    let mut warnings = Vec::new();
+    // (UID, original start) of occurrences moved by a RECURRENCE-ID override
+    let mut overrides: Vec<(String, DateTime<Local>)> = Vec::new();
+    let mut properties: Option<Vec<(String, String, String)>> = None;
+    // Components open inside the current event, such as VALARM; their
+    // properties aren't the event's
+    let mut nested = 0;
+    // The first unreadable line of the current event
+    let mut broken: Option<String> = None;
+
+    for line in unfold(text) {
+        let (name, params, value) = match split_property(&line) {
+            Ok(property) => property,
+            Err(e) if properties.is_some() => {
+                broken.get_or_insert(e);
+                continue;
+            }
+            Err(e) => {
+                warnings.push(e);
+                continue;
+            }
+        };
+        match (name.as_str(), value.as_str()) {
+            ("BEGIN", "VEVENT") if properties.is_none() => {
+                properties = Some(Vec::new());
+                broken = None;
+            }
+            ("BEGIN", _) if properties.is_some() => nested += 1,
+            ("END", _) if nested > 0 => nested -= 1,
+            ("END", "VEVENT") => {
+                let Some(properties) = properties.take() else {
+                    continue;
+                };
+                match broken.take().map_or_else(|| read_event(&properties), Err) {
+                    Ok(Some(event)) => {
+                        if let Some(original) = event.recurrence_id {
+                            overrides.push((event.event.uid.clone(), original));
+                        }
+                        events.push(event);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warnings.push(format!("Skipped event '{}': {}", event_name(&properties), e)),
+                }
+            }
+            _ if nested > 0 => {}
+            _ => {
+                if let Some(properties) = properties.as_mut() {
+                    properties.push((name, params, value));
+                }
+            }
+        }
+    }
+
+    for (uid, original) in overrides {
+        for event in events.iter_mut() {
+            if event.event.uid == uid && event.recurrence.is_some() {
+                event.exceptions.push(original);
+            }
+        }
+    }
+    (events, warnings)
+}
+
+/// The summary or UID of an event, to name it in warnings
+fn event_name(properties: &[(String, String, String)]) -> String {
+    let get = |wanted: &str| properties.iter().find(|(name, _, _)| name == wanted);
+    get("SUMMARY")
+        .or_else(|| get("UID"))
+        .map(|(_, _, value)| unescape(value))
+        .unwrap_or_else(|| "untitled".to_string())
+}
+
+/// Joins folded lines (continuations start with a space or tab)
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits "NAME;PARAM=x:value" into (NAME, PARAM=x, value). Colons inside
+/// quoted parameter values don't end the name.
+fn split_property(line: &str) -> Result<(String, String, String), String> {
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => {
+                let (head, value) = (&line[..index], &line[index + 1..]);
+                let (name, params) = head.split_once(';').unwrap_or((head, ""));
+                return Ok((name.to_uppercase(), params.to_string(), value.to_string()));
+            }
+            _ => {}
+        }
+    }
+    Err(format!("Invalid calendar line: {}", line))
+}
+
+/// Builds an event from its properties. Returns `None` for events that take
+/// up no time.
+fn read_event(properties: &[(String, String, String)]) -> Result<Option<CalendarEvent>, String> {
+    let get = |wanted: &str| properties.iter().find(|(name, _, _)| name == wanted);
+
+    if matches!(get("STATUS"), Some((_, _, status)) if status.eq_ignore_ascii_case("CANCELLED")) {
+        return Ok(None);
+    }
+    if matches!(get("TRANSP"), Some((_, _, transp)) if transp.eq_ignore_ascii_case("TRANSPARENT")) {
+        return Ok(None);
+    }
+    let Some((_, params, value)) = get("DTSTART") else {
+        return Ok(None);
+    };
+    let Some((start, zone)) = parse_zoned(params, value)? else {
+        // All-day event
+        return Ok(None);
+    };
+    let start = zone
+        .resolve(start)
+        .ok_or_else(|| format!("{} does not exist in its time zone", value))?;
+    let end = match (get("DTEND"), get("DURATION")) {
+        (Some((_, params, value)), _) => parse_time(params, value)?,
+        (None, Some((_, _, value))) => Some(
+            start
+                .checked_add_signed(parse_duration(value)?)
+                .ok_or_else(|| format!("Calendar duration out of range: {}", value))?,
+        ),
+        (None, None) => Some(start),
+    };
+    let Some(end) = end.filter(|end| *end > start) else {
+        return Ok(None);
+    };
+
+    let recurrence = match get("RRULE") {
+        Some((_, _, rule)) => parse_rule(rule)?,
+        None => None,
+    };
+    let mut exceptions = Vec::new();
+    for (_, params, value) in properties.iter().filter(|(name, _, _)| name == "EXDATE") {
+        for value in value.split(',') {
+            if let Some(time) = parse_time(params, value)? {
+                exceptions.push(time);
+            }
+        }
+    }
+    let recurrence_id = match get("RECURRENCE-ID") {
+        Some((_, params, value)) => parse_time(params, value)?,
+        None => None,
+    };
+
+    let text = |name: &str| get(name).map(|(_, _, value)| unescape(value));
+    let event = Event {
+        uid: text("UID").unwrap_or_else(|| format!("{}@local", start.timestamp())),
+        start,
+        end,
+        summary: text("SUMMARY").unwrap_or_else(|| "Untitled event".to_string()),
+        description: text("DESCRIPTION"),
+        categories: text("CATEGORIES")
+            .map(|categories| categories.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+    };
+    Ok(Some(CalendarEvent {
+        event,
+        zone,
+        recurrence,
+        exceptions,
+        recurrence_id,
+    }))
+}
+
+/// The value of the parameter `name` in e.g. `TZID="Europe/Berlin";VALUE=DATE-TIME`
+fn param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+    params
+        .split(';')
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim_matches('"'))
+}
+
+/// The time zone of a TZID: an IANA name, possibly behind a prefix such as
+/// "/mozilla.org/20050126_1/", or a Windows name
+fn time_zone(tzid: &str) -> Result<Tz, String> {
+    let tzid = tzid.trim();
+    let iana = |name: &str| name.parse::<Tz>().ok();
+    iana(tzid)
+        .or_else(|| tzid.match_indices('/').find_map(|(index, _)| iana(&tzid[index + 1..])))
+        .or_else(|| {
+            WINDOWS_ZONES
+                .iter()
+                .find(|(windows, _)| windows.eq_ignore_ascii_case(tzid))
+                .and_then(|(_, name)| iana(name))
+        })
+        .ok_or_else(|| format!("Unknown time zone '{}'", tzid))
+}
+
+/// Parses a DATE-TIME value into its wall-clock time and time zone; returns
+/// `None` for a plain DATE
+fn parse_zoned(params: &str, value: &str) -> Result<Option<(NaiveDateTime, Zone)>, String> {
+    let value = value.trim();
+    let invalid = |_| format!("Invalid calendar time: {}", value);
+    let is_date = value.len() == 8 || param(params, "VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"));
+    if is_date {
+        NaiveDate::parse_from_str(value, "%Y%m%d").map_err(invalid)?;
+        return Ok(None);
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(invalid)?;
+        return Ok(Some((time, Zone::Named(Tz::UTC))));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(invalid)?;
+    let zone = match param(params, "TZID") {
+        Some(tzid) => Zone::Named(time_zone(tzid)?),
+        None => Zone::Floating,
+    };
+    Ok(Some((time, zone)))
+}
+
+/// Parses a DATE-TIME value; returns `None` for a plain DATE
+fn parse_time(params: &str, value: &str) -> Result<Option<DateTime<Local>>, String> {
+    match parse_zoned(params, value)? {
+        Some((time, zone)) => zone
+            .resolve(time)
+            .map(Some)
+            .ok_or_else(|| format!("{} does not exist in its time zone", value.trim())),
+        None => Ok(None),
+    }
+}
+
+/// Parses a DURATION value such as "PT1H30M" or "P1D"
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid calendar duration: {}", value);
+    let (negative, rest) = match value.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim().trim_start_matches('+')),
+    };
+    let rest = rest.strip_prefix('P').ok_or_else(invalid)?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let amount: i64 = number.parse().map_err(|_| invalid())?;
+                number.clear();
+                let part = match c {
+                    'W' => Duration::try_weeks(amount),
+                    'D' => Duration::try_days(amount),
+                    'H' => Duration::try_hours(amount),
+                    'M' => Duration::try_minutes(amount),
+                    _ => Duration::try_seconds(amount),
+                };
+                total = part.and_then(|part| total.checked_add(&part)).ok_or_else(invalid)?;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(if negative { -total } else { total })
+}
+
+/// Parses an RRULE; rules other than daily and weekly return `None`
+fn parse_rule(rule: &str) -> Result<Option<Recurrence>, String> {
+    let mut frequency = None;
+    let mut recurrence = Recurrence {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+    };
+    let invalid = || format!("Invalid recurrence rule: {}", rule);
+
+    for part in rule.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_uppercase().as_str() {
+            "FREQ" => {
+                frequency = match value.to_uppercase().as_str() {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    _ => return Ok(None),
+                }
+            }
+            "INTERVAL" => recurrence.interval = value.parse().map_err(|_| invalid())?,
+            "COUNT" => recurrence.count = Some(value.parse().map_err(|_| invalid())?),
+            "UNTIL" => {
+                recurrence.until = match parse_time("", value)? {
+                    Some(until) => Some(until),
+                    // A date means the whole day is included
+                    None => NaiveDate::parse_from_str(value, "%Y%m%d")
+                        .ok()
+                        .and_then(|date| date.and_hms_opt(23, 59, 59))
+                        .and_then(|until| Local.from_local_datetime(&until).earliest()),
+                }
+            }
+            "BYDAY" => {
+                for day in value.split(',') {
+                    // Ordinals such as "1MO" only occur in monthly rules
+                    let day = day.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '+');
+                    let weekday = match day.to_uppercase().as_str() {
+                        "MO" => Weekday::Mon,
+                        "TU" => Weekday::Tue,
+                        "WE" => Weekday::Wed,
+                        "TH" => Weekday::Thu,
+                        "FR" => Weekday::Fri,
+                        "SA" => Weekday::Sat,
+                        "SU" => Weekday::Sun,
+                        _ => return Err(invalid()),
+                    };
+                    recurrence.by_day.push(weekday);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(frequency.map(|frequency| Recurrence { frequency, ..recurrence }))
+}
+
+/// Reverses `escape`
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A calendar of the given lines between BEGIN/END:VCALENDAR, with CRLF
+    /// line ends as in real files
+    fn calendar(lines: &[&str]) -> String {
+        let mut text = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n".to_string();
+        for line in lines {
+            text.push_str(line);
+            text.push_str("\r\n");
+        }
+        text + "END:VCALENDAR\r\n"
+    }
+
+    /// The events of a calendar that must parse without warnings
+    fn parse(lines: &[&str]) -> Vec<CalendarEvent> {
+        let (events, warnings) = parse_calendar(&calendar(lines));
+        assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+        events
+    }
+
+    fn local(text: &str) -> DateTime<Local> {
+        let time = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+        Local.from_local_datetime(&time).unwrap()
+    }
+
+    fn starts(events: &[Event]) -> Vec<DateTime<Local>> {
+        events.iter().map(|event| event.start).collect()
+    }
+
+    #[test]
+    fn alarm_properties_stay_out_of_the_event() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "UID:standup",
+            "DTSTART:20261019T093000",
+            "BEGIN:VALARM",
+            "ACTION:DISPLAY",
+            "SUMMARY:Alarm summary",
+            "DESCRIPTION:Reminder",
+            "TRIGGER:-PT10M",
+            "DURATION:PT5M",
+            "END:VALARM",
+            "DTEND:20261019T094500",
+            "SUMMARY:Standup",
+            "END:VEVENT",
+        ]);
+        assert_eq!(events.len(), 1);
+        let event = &events[0].event;
+        assert_eq!(event.summary, "Standup");
+        assert_eq!(event.description, None);
+        assert_eq!(event.start, local("2026-10-19 09:30"));
+        assert_eq!(event.end, local("2026-10-19 09:45"));
+    }
+
+    #[test]
+    fn an_alarm_duration_is_not_the_event_length() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "DTSTART:20261019T100000",
+            "DURATION:PT1H",
+            "BEGIN:VALARM",
+            "DURATION:PT5M",
+            "REPEAT:2",
+            "END:VALARM",
+            "END:VEVENT",
+        ]);
+        assert_eq!(events[0].event.end, local("2026-10-19 11:00"));
+    }
+
+    #[test]
+    fn folded_lines_are_joined() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "DTSTART:20261019T100000",
+            "DTEND:20261019T110000",
+            "SUMMARY:Quarterly planning with the",
+            "  platform team",
+            "DESCRIPTION:Agenda\\, notes",
+            "\tand\\nfollow-ups",
+            "END:VEVENT",
+        ]);
+        let event = &events[0].event;
+        assert_eq!(event.summary, "Quarterly planning with the platform team");
+        assert_eq!(event.description.as_deref(), Some("Agenda, notesand\nfollow-ups"));
+    }
+
+    #[test]
+    fn written_calendars_read_back() {
+        let event = Event {
+            uid: "session-1@timesense".to_string(),
+            start: local("2026-10-19 09:00"),
+            end: local("2026-10-19 10:15"),
+            summary: format!("Deep work: {}", "x".repeat(100)),
+            description: Some("Editor; terminal, browser\nand notes".to_string()),
+            categories: vec!["productive".to_string()],
+        };
+        let text = write_calendar(std::slice::from_ref(&event));
+        assert!(text.lines().all(|line| line.len() <= MAX_LINE_OCTETS));
+
+        // Exported events are free time, so they read back as nothing
+        let (events, warnings) = parse_calendar(&text.replace("TRANSP:TRANSPARENT", "TRANSP:OPAQUE"));
+        assert!(warnings.is_empty());
+        let parsed = &events[0].event;
+        assert_eq!(parsed.summary, event.summary);
+        assert_eq!(parsed.description, event.description);
+        assert_eq!((parsed.start, parsed.end), (event.start, event.end));
+        assert!(parse_calendar(&text).0.is_empty());
+    }
+
+    #[test]
+    fn durations_give_the_end() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "DTSTART:20261019T100000",
+            "DURATION:PT1H30M",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "DTSTART:20261019T100000",
+            "DURATION:P1DT2H",
+            "END:VEVENT",
+        ]);
+        assert_eq!(events[0].event.end, local("2026-10-19 11:30"));
+        assert_eq!(events[1].event.end, local("2026-10-20 12:00"));
+        assert_eq!(parse_duration("-PT15M"), Ok(Duration::minutes(-15)));
+        assert_eq!(parse_duration("P2W"), Ok(Duration::weeks(2)));
+        assert!(parse_duration("1H").is_err());
+    }
+
+    #[test]
+    fn huge_durations_and_intervals_are_not_a_crash() {
+        assert!(parse_duration("P99999999999W").is_err());
+        assert!(parse_duration("PT9223372036854775807S").is_err());
+        assert!(parse_duration("P9000000000W9000000000W").is_err());
+
+        let (events, warnings) = parse_calendar(&calendar(&[
+            "BEGIN:VEVENT",
+            "SUMMARY:Forever",
+            "DTSTART:20261019T100000",
+            "DURATION:P99999999999W",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Until the end of time",
+            "DTSTART:20261019T100000",
+            "DURATION:P20000000W",
+            "END:VEVENT",
+        ]));
+        assert!(events.is_empty());
+        assert_eq!(warnings.len(), 2);
+
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "DTSTART:20261019T100000",
+            "DTEND:20261019T110000",
+            "RRULE:FREQ=DAILY;INTERVAL=4000000000",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "DTSTART:20261019T100000",
+            "DTEND:20261019T110000",
+            "RRULE:FREQ=WEEKLY;INTERVAL=4000000000;BYDAY=MO,TU",
+            "END:VEVENT",
+        ]);
+        let (from, to) = (local("2026-10-01 00:00"), local("2026-11-01 00:00"));
+        assert_eq!(starts(&events[0].occurrences(from, to)), vec![local("2026-10-19 10:00")]);
+        assert_eq!(
+            starts(&events[1].occurrences(from, to)),
+            vec![local("2026-10-19 10:00"), local("2026-10-20 10:00")]
+        );
+    }
+
+    #[test]
+    fn all_day_cancelled_free_and_instant_events_are_skipped() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "SUMMARY:Holiday",
+            "DTSTART;VALUE=DATE:20261019",
+            "DTEND;VALUE=DATE:20261020",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Date without VALUE",
+            "DTSTART:20261019",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Cancelled",
+            "DTSTART:20261019T100000",
+            "DTEND:20261019T110000",
+            "STATUS:CANCELLED",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Free",
+            "DTSTART:20261019T100000",
+            "DTEND:20261019T110000",
+            "TRANSP:TRANSPARENT",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Reminder",
+            "DTSTART:20261019T100000",
+            "END:VEVENT",
+        ]);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn weekly_rules_with_exdate_and_a_moved_occurrence() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "UID:sync",
+            "SUMMARY:Team sync",
+            "DTSTART:20261019T100000",
+            "DTEND:20261019T103000",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=6",
+            "EXDATE:20261021T100000,20261026T100000",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:sync",
+            "SUMMARY:Team sync (moved)",
+            "RECURRENCE-ID:20261028T100000",
+            "DTSTART:20261029T140000",
+            "DTEND:20261029T143000",
+            "END:VEVENT",
+        ]);
+        let from = local("2026-10-01 00:00");
+        let to = local("2026-12-01 00:00");
+        let occurrences: Vec<Event> = events.iter().flat_map(|event| event.occurrences(from, to)).collect();
+        // Six occurrences Mon 19, Wed 21, Mon 26, Wed 28, Mon 2, Wed 4: two
+        // cancelled and one moved to Thursday
+        assert_eq!(
+            starts(&occurrences),
+            vec![
+                local("2026-10-19 10:00"),
+                local("2026-11-02 10:00"),
+                local("2026-11-04 10:00"),
+                local("2026-10-29 14:00"),
+            ]
+        );
+        assert_eq!(occurrences[3].summary, "Team sync (moved)");
+    }
+
+    #[test]
+    fn daily_rules_with_interval_and_until() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "DTSTART:20261019T090000",
+            "DTEND:20261019T091500",
+            "RRULE:FREQ=DAILY;INTERVAL=2;UNTIL=20261025",
+            "END:VEVENT",
+        ]);
+        let occurrences = events[0].occurrences(local("2026-10-20 00:00"), local("2026-11-01 00:00"));
+        assert_eq!(
+            starts(&occurrences),
+            vec![local("2026-10-21 09:00"), local("2026-10-23 09:00"), local("2026-10-25 09:00")]
+        );
+    }
+
+    #[test]
+    fn unsupported_rules_are_single_events() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "DTSTART:20261019T090000",
+            "DTEND:20261019T100000",
+            "RRULE:FREQ=MONTHLY;BYDAY=1MO",
+            "END:VEVENT",
+        ]);
+        let occurrences = events[0].occurrences(local("2026-10-01 00:00"), local("2027-01-01 00:00"));
+        assert_eq!(starts(&occurrences), vec![local("2026-10-19 09:00")]);
+    }
+
+    #[test]
+    fn unreadable_events_are_skipped_with_a_warning() {
+        let (events, warnings) = parse_calendar(&calendar(&[
+            "BEGIN:VEVENT",
+            "SUMMARY:Bad start",
+            "DTSTART:2026-10-19 10:00",
+            "DTEND:20261019T110000",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Bad exception",
+            "DTSTART:20261019T100000",
+            "DTEND:20261019T110000",
+            "RRULE:FREQ=DAILY",
+            "EXDATE:tomorrow",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:no-colon",
+            "DTSTART:20261019T100000",
+            "this line has no value",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Fine",
+            "DTSTART:20261019T120000",
+            "DTEND:20261019T130000",
+            "END:VEVENT",
+        ]));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.summary, "Fine");
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].starts_with("Skipped event 'Bad start'"));
+        assert!(warnings[1].starts_with("Skipped event 'Bad exception'"));
+        assert!(warnings[2].starts_with("Skipped event 'no-colon'"));
+    }
+
+    fn utc(text: &str) -> DateTime<Local> {
+        let time = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+        Utc.from_utc_datetime(&time).with_timezone(&Local)
+    }
+
+    #[test]
+    fn times_are_read_in_their_time_zone() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "SUMMARY:IANA",
+            "DTSTART;TZID=America/New_York:20261019T090000",
+            "DTEND;TZID=America/New_York:20261019T100000",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Quoted with a prefix",
+            "DTSTART;TZID=\"/mozilla.org/20050126_1/Europe/Berlin\":20261019T090000",
+            "DTEND;TZID=\"/mozilla.org/20050126_1/Europe/Berlin\":20261019T100000",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:Windows",
+            "DTSTART;TZID=Tokyo Standard Time:20261019T090000",
+            "DTEND;TZID=Tokyo Standard Time:20261019T100000",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "SUMMARY:UTC",
+            "DTSTART:20261019T090000Z",
+            "DTEND:20261019T100000Z",
+            "END:VEVENT",
+        ]);
+        let events: Vec<Event> = events.into_iter().map(|event| event.event).collect();
+        assert_eq!(
+            starts(&events),
+            vec![
+                utc("2026-10-19 13:00"),
+                utc("2026-10-19 07:00"),
+                utc("2026-10-19 00:00"),
+                utc("2026-10-19 09:00"),
+            ]
+        );
+        assert_eq!(events[0].end, utc("2026-10-19 14:00"));
+    }
+
+    #[test]
+    fn recurrences_repeat_on_the_wall_clock_of_their_time_zone() {
+        // New York leaves daylight saving time on 2026-11-01
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "DTSTART;TZID=America/New_York:20261026T090000",
+            "DTEND;TZID=America/New_York:20261026T093000",
+            "RRULE:FREQ=WEEKLY;COUNT=3",
+            "EXDATE;TZID=America/New_York:20261109T090000",
+            "END:VEVENT",
+        ]);
+        let occurrences = events[0].occurrences(utc("2026-10-01 00:00"), utc("2026-12-01 00:00"));
+        assert_eq!(starts(&occurrences), vec![utc("2026-10-26 13:00"), utc("2026-11-02 14:00")]);
+    }
+
+    #[test]
+    fn events_in_an_unknown_time_zone_are_skipped_with_a_warning() {
+        let (events, warnings) = parse_calendar(&calendar(&[
+            "BEGIN:VEVENT",
+            "SUMMARY:Nowhere",
+            "DTSTART;TZID=Mars/Olympus_Mons:20261019T090000",
+            "DTEND;TZID=Mars/Olympus_Mons:20261019T100000",
+            "END:VEVENT",
+        ]));
+        assert!(events.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Unknown time zone 'Mars/Olympus_Mons'"));
+    }
+}
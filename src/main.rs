@@ -1,4 +1,5 @@
-use chrono::{DateTime, Duration, Local};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use clap::Parser;
 use ctrlc;
 use device_query::{DeviceQuery, DeviceState};
 use screenshots::Screen;
@@ -6,29 +7,104 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time;
 use webbrowser;
 
+mod activitywatch;
+mod api;
 mod app_detector;
+mod calendar;
+mod cli;
+mod commits;
+mod config;
+mod config_watcher;
+mod control;
+mod corrections;
+mod day_boundary;
+mod export;
+mod goals;
+mod heatmap;
+mod ics;
+mod metrics;
+mod notifier;
+mod period_report;
+mod project_resolver;
+mod recategorize;
 mod report_generator;
+mod scoring;
 mod screenshot_analyzer;
+mod storage;
+mod templates;
+mod time_format;
+mod timeline;
+mod timesheet;
+mod timewarrior;
 mod fuzzy_match;
 
+use api::{ApiServer, LiveState};
 use app_detector::{AppDetector, PlatformAppDetector};
+use cli::{Cli, Command};
+use config::Config;
+use config_watcher::ConfigWatcher;
+use control::{ControlCommand, ControlSocket, ControlState};
+use metrics::FocusMetrics;
+use notifier::Notifier;
 use report_generator::ReportGenerator;
 use screenshot_analyzer::ScreenshotAnalyzer;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How often the open block is saved while it grows; finished blocks are
+/// saved right away
+const OPEN_BLOCK_SAVE_INTERVAL: time::Duration = time::Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct TimeBlock {
     start_time: DateTime<Local>,
     end_time: DateTime<Local>,
     application: String,
     activity_type: String,
     idle: bool,
+    /// Most recent title of the focused window while the block was open
+    #[serde(default)]
+    window_title: Option<String>,
+    #[serde(default)]
+    project: Option<String>,
+    /// Branch checked out in the project's git repository
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    notes: Vec<String>,
+    /// Entered by hand rather than recorded by the tracker
+    #[serde(default)]
+    manual: bool,
+    /// Title of the calendar meeting the block falls in
+    #[serde(default)]
+    meeting: Option<String>,
+}
+
+impl TimeBlock {
+    /// Starts a new, still open block at `start_time`
+    fn new(start_time: DateTime<Local>, application: String, activity_type: String, idle: bool) -> Self {
+        TimeBlock {
+            start_time,
+            end_time: start_time, // Will be updated later
+            application,
+            activity_type,
+            idle,
+            window_title: None,
+            project: None,
+            branch: None,
+            tags: Vec::new(),
+            notes: Vec::new(),
+            manual: false,
+            meeting: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -37,20 +113,183 @@ struct DailySummary {
     productive_time: Duration,
     distracted_time: Duration,
     idle_time: Duration,
+    /// Active time that is neither productive nor a distraction
+    #[serde(default)]
+    neutral_time: Duration,
+    /// Sum of all block durations; equals the four totals above combined
+    #[serde(default)]
+    tracked_time: Duration,
     application_breakdown: HashMap<String, Duration>,
     activity_breakdown: HashMap<String, Duration>,
+    /// Active time per category; unlike `activity_breakdown` it leaves out
+    /// idle time
+    #[serde(default)]
+    category_breakdown: HashMap<String, Duration>,
+    #[serde(default)]
+    project_breakdown: HashMap<String, Duration>,
+    #[serde(default)]
+    focus_metrics: FocusMetrics,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Config {
-    screenshot_interval_seconds: u64,
-    idle_threshold_seconds: u64,
-    productive_apps: Vec<String>,
-    distraction_apps: Vec<String>,
-    data_directory: String,
+fn main() {
+    let cli = Cli::parse();
+    let (config, config_path) = load_or_create_config();
+
+    match cli.command {
+        None | Some(Command::Track) => run_tracker(config, config_path),
+        Some(Command::Recategorize { from, to, dry_run }) => {
+            let to = to.unwrap_or_else(|| day_boundary::logical_date(Local::now(), config.day_start));
+            exit_on_error(recategorize::run(&config, from, to, dry_run));
+        }
+        Some(Command::Report {
+            week,
+            month,
+            from,
+            to,
+            heatmap_category,
+        }) => {
+            let period = match (week, month, from.zip(to)) {
+                (Some(week), _, _) => period_report::Period::parse_week(&week),
+                (None, Some(month), _) => period_report::Period::parse_month(&month),
+                (None, None, Some((from, to))) => period_report::Period::range(from, to),
+                _ => Err("Pass --week, --month or --from/--to".to_string()),
+            };
+            exit_on_error(
+                period.and_then(|period| generate_period_report(&config, period, heatmap_category)),
+            );
+        }
+        Some(Command::Blocks { date, action }) => {
+            let date = date.unwrap_or_else(|| day_boundary::logical_date(Local::now(), config.day_start));
+            exit_on_error(corrections::run(&config, date, action));
+        }
+        Some(Command::Goals { date }) => {
+            exit_on_error(goals::run(&config, date));
+        }
+        Some(Command::NotifyTest) => {
+            exit_on_error(notifier::run_test());
+        }
+        Some(Command::Export {
+            format,
+            from,
+            to,
+            granularity,
+            min_session,
+            categories,
+            output,
+        }) => {
+            let to = to.unwrap_or_else(|| day_boundary::logical_date(Local::now(), config.day_start));
+            let filter = export::SessionFilter {
+                min_length: Duration::minutes(min_session as i64),
+                categories,
+            };
+            exit_on_error(export::run(&config, from, to, format, granularity, &filter, output.as_deref()));
+        }
+        Some(Command::Import {
+            format,
+            file,
+            replace,
+            dry_run,
+        }) => match format {
+            cli::ImportFormat::ActivityWatch => {
+                exit_on_error(activitywatch::import(&config, &file, replace, dry_run));
+            }
+            cli::ImportFormat::Timewarrior if replace => {
+                exit_on_error(Err("--replace only applies to ActivityWatch imports; Timewarrior intervals are added as manual blocks".to_string()));
+            }
+            cli::ImportFormat::Timewarrior => {
+                exit_on_error(timewarrior::import(&config, &file, dry_run));
+            }
+        },
+        Some(Command::Timesheet {
+            tool,
+            from,
+            to,
+            round_minutes,
+            min_minutes,
+            merge_gap_minutes,
+            output,
+        }) => {
+            let to = to.unwrap_or_else(|| day_boundary::logical_date(Local::now(), config.day_start));
+            let mut settings = config.timesheet.clone();
+            settings.round_to_minutes = round_minutes.unwrap_or(settings.round_to_minutes);
+            settings.min_entry_minutes = min_minutes.unwrap_or(settings.min_entry_minutes);
+            settings.merge_gap_minutes = merge_gap_minutes.unwrap_or(settings.merge_gap_minutes);
+            exit_on_error(timesheet::run(&config, &settings, from, to, tool, output.as_deref()));
+        }
+        Some(Command::Commits { repos, from, to, author }) => {
+            let to = to.unwrap_or_else(|| day_boundary::logical_date(Local::now(), config.day_start));
+            exit_on_error(commits::run(&config, &repos, from, to, author.as_deref()));
+        }
+        Some(Command::Pause { length }) => {
+            exit_on_error(control::send(&config, &ControlCommand::Pause { length }));
+        }
+        Some(Command::Resume) => {
+            exit_on_error(control::send(&config, &ControlCommand::Resume));
+        }
+        Some(Command::Tag { label, clear }) => {
+            let command = match (label, clear) {
+                (_, true) => Ok(ControlCommand::Tag { label: None }),
+                (Some(label), false) if !label.trim().is_empty() => Ok(ControlCommand::Tag {
+                    label: Some(label.trim().to_string()),
+                }),
+                _ => Err("The tag label must not be empty; use --clear to stop tagging".to_string()),
+            };
+            exit_on_error(command.and_then(|command| control::send(&config, &command)));
+        }
+        Some(Command::StartFocus { length }) => {
+            exit_on_error(control::send(&config, &ControlCommand::StartFocus { length }));
+        }
+        Some(Command::Stop) => {
+            exit_on_error(control::send(&config, &ControlCommand::Stop));
+        }
+    }
 }
 
-fn main() {
+/// Prints the error and exits with a failure status
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn generate_period_report(
+    config: &Config,
+    period: period_report::Period,
+    heatmap_category: Option<String>,
+) -> Result<(), String> {
+    println!(
+        "Generating report for {} ({} to {})...",
+        period.label, period.start, period.end
+    );
+    for path in period_report::generate(config, period, heatmap_category.as_deref())? {
+        println!("Wrote {}", path);
+    }
+    Ok(())
+}
+
+/// Loads the config from the working directory, writing the default config if
+/// none exists yet. Exits if an existing config is invalid.
+fn load_or_create_config() -> (Config, PathBuf) {
+    let config_path = config::locate().unwrap_or_else(|| PathBuf::from(config::DEFAULT_CONFIG_PATH));
+    let config = if config_path.exists() {
+        config::load(&config_path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        })
+    } else {
+        let default_config = Config::default();
+
+        // Save default config
+        config::save(&default_config, &config_path).expect("Failed to save default config");
+
+        default_config
+    };
+
+    (config, config_path)
+}
+
+fn run_tracker(mut config: Config, config_path: PathBuf) {
     println!("Starting TimeSense - Automated Time Awareness Tool");
 
     // Set up signal handler for graceful shutdown
@@ -63,48 +302,14 @@ fn main() {
     })
     .expect("Error setting Ctrl+C handler");
 
-    // Load config or create default
-    let config = load_config().unwrap_or_else(|| {
-        let default_config = Config {
-            screenshot_interval_seconds: 60,
-            idle_threshold_seconds: 180,
-            productive_apps: vec![
-                "code".to_string(),
-                "terminal".to_string(),
-                "notion".to_string(),
-                "cursor".to_string(),
-                "vscode".to_string(),
-            ],
-            distraction_apps: vec![
-                "twitter".to_string(),
-                "youtube".to_string(),
-                "reddit".to_string(),
-                "slack".to_string(),
-                "discord".to_string(),
-                "spotify".to_string(),
-                "steam".to_string(),
-                "twitch".to_string(),
-                "telegram".to_string(),
-                "whatsapp".to_string(),
-                "zoom".to_string(),
-                "skype".to_string(),
-                "microsoft teams".to_string(),
-                "google meet".to_string(),
-            ],
-            data_directory: "timesense_data".to_string(),
-        };
-
-        // Save default config
-        save_config(&default_config).expect("Failed to save default config");
-
-        default_config
-    });
+    // Reload the config when the file changes or on SIGHUP
+    let config_watcher = ConfigWatcher::new(&config_path);
 
     // Create data directory if it doesn't exist
     fs::create_dir_all(&config.data_directory).expect("Failed to create data directory");
 
     // Create screenshots subdirectory
-    let screenshots_dir = Path::new(&config.data_directory).join("screenshots");
+    let mut screenshots_dir = Path::new(&config.data_directory).join("screenshots");
     fs::create_dir_all(&screenshots_dir).expect("Failed to create screenshots directory");
 
     // Initialize screenshot analyzer (for fallback)
@@ -114,16 +319,91 @@ fn main() {
     // Initialize the app detector
     let app_detector = PlatformAppDetector::new();
 
-    let mut time_blocks: Vec<TimeBlock> = Vec::new();
+    // Continue today's stored blocks so a restart doesn't overwrite them
+    let mut current_day = day_boundary::logical_date(Local::now(), config.day_start);
+    let mut time_blocks: Vec<TimeBlock> = storage::load_blocks(&config.data_directory, current_day)
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
     let mut current_block: Option<TimeBlock> = None;
+    // Day and number of finished blocks at the last save, and when it was
+    let mut last_save: Option<(NaiveDate, usize, time::Instant)> = None;
     let device_state = DeviceState::new();
     let mut last_input_time = Local::now();
+    let mut notifier = Notifier::new();
+
+    // Serve the local API from the tracker's state, if enabled
+    let api_state = Arc::new(Mutex::new(LiveState {
+        config: config.clone(),
+        date: current_day,
+        current_block: None,
+        updated_at: None,
+        paused: false,
+    }));
+    let mut api_server = ApiServer::start(&config.api, &api_state);
+
+    // Let scripts pause, tag and stop the tracker
+    let mut control_state = ControlState::default();
+    let mut control_socket = ControlSocket::start(&control::socket_path(&config));
 
     println!("TimeSense is running. Press Ctrl+C to stop and generate a report.");
 
     while running.load(Ordering::SeqCst) {
+        if config_watcher.take_reload_request() {
+            let api_settings = config.api.clone();
+            let control_path = control::socket_path(&config);
+            if reload_config(&mut config, &config_path) {
+                calendar::clear_cache();
+                goals::clear_cache();
+                screenshots_dir = Path::new(&config.data_directory).join("screenshots");
+                api_state.lock().unwrap().config = config.clone();
+                if config.api != api_settings {
+                    // Stop the old server first so its address is free again
+                    drop(api_server.take());
+                    api_server = ApiServer::start(&config.api, &api_state);
+                }
+                if control::socket_path(&config) != control_path {
+                    drop(control_socket.take());
+                    control_socket = ControlSocket::start(&control::socket_path(&config));
+                }
+            }
+        }
+
         let now = Local::now();
 
+        let requests = control_socket.as_mut().map(ControlSocket::take_requests).unwrap_or_default();
+        for request in requests {
+            println!("Control command: {:?}", request.command);
+            let answer = control_state.apply(&request.command, now);
+            match request.command {
+                _ if answer.is_err() => {}
+                ControlCommand::Pause { .. } => {
+                    // Close the open block now instead of at the next sample
+                    finish_elapsed_days(&config, now, &mut current_day, &mut time_blocks, &mut current_block);
+                    if let Some(mut block) = current_block.take() {
+                        block.end_time = now;
+                        time_blocks.push(block);
+                        save_day_blocks(&config, current_day, &time_blocks);
+                    }
+                    let mut state = api_state.lock().unwrap();
+                    state.current_block = None;
+                    state.paused = true;
+                }
+                ControlCommand::Stop => running.store(false, Ordering::SeqCst),
+                _ => {}
+            }
+            request.reply(answer);
+        }
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        if control_state.is_paused(now) {
+            // Nothing is recorded until a resume, a reload or the end of the pause
+            thread::sleep(time::Duration::from_millis(250));
+            continue;
+        }
+
         // Check for user activity
         let keys_pressed = device_state.get_keys();
         let is_active = !keys_pressed.is_empty();
@@ -137,9 +417,26 @@ fn main() {
 
         // Get the active application
         let active_app = app_detector.get_active_application();
+        let window_title = app_detector.get_active_window_title();
         println!("Active application: {}", active_app);
 
-        let activity_type = categorize_activity(&active_app, &config);
+        // Idle time isn't attributed to a project
+        let repository = if is_idle {
+            None
+        } else {
+            config
+                .projects
+                .resolve(&active_app, window_title.as_deref(), app_detector.get_active_pid())
+        };
+        let (project, branch) = match repository {
+            Some(repository) => {
+                println!("Project: {} ({})", repository.name, repository.branch.as_deref().unwrap_or("-"));
+                (Some(repository.name), repository.branch)
+            }
+            None => (None, None),
+        };
+
+        let activity_type = categorize_activity(&active_app, project.as_deref(), &config);
         println!("Activity type: {}", activity_type);
 
         // Take a screenshot for record-keeping
@@ -172,36 +469,41 @@ fn main() {
             Err(e) => println!("Failed to get screens: {}", e),
         }
 
+        finish_elapsed_days(
+            &config,
+            now,
+            &mut current_day,
+            &mut time_blocks,
+            &mut current_block,
+        );
+
         // Update time blocks
+        let mut next_block = TimeBlock::new(now, active_app.clone(), activity_type.clone(), is_idle);
+        next_block.project = project.clone();
+        next_block.branch = branch.clone();
+        next_block.tags = control_state.tags(now);
         match &current_block {
             Some(block) => {
-                if block.application != active_app || block.idle != is_idle {
+                if block.application != active_app
+                    || block.idle != is_idle
+                    || block.project != project
+                    || block.branch != branch
+                    || block.tags != next_block.tags
+                {
                     // Finish current block
                     let mut finished_block = current_block.take().unwrap();
                     finished_block.end_time = now;
                     time_blocks.push(finished_block);
 
                     // Start new block
-                    current_block = Some(TimeBlock {
-                        start_time: now,
-                        end_time: now, // Will be updated later
-                        application: active_app.clone(),
-                        activity_type: activity_type.clone(),
-                        idle: is_idle,
-                    });
+                    current_block = Some(next_block);
 
                     println!("New time block started: {} ({})", active_app, activity_type);
                 }
             }
             None => {
                 // Start first block
-                current_block = Some(TimeBlock {
-                    start_time: now,
-                    end_time: now, // Will be updated later
-                    application: active_app.clone(),
-                    activity_type: activity_type.clone(),
-                    idle: is_idle,
-                });
+                current_block = Some(next_block);
 
                 println!(
                     "First time block started: {} ({})",
@@ -210,33 +512,87 @@ fn main() {
             }
         }
 
-        // Generate daily summary if it's a new day
-        if !time_blocks.is_empty()
-            && time_blocks.last().unwrap().end_time.date_naive() != now.date_naive()
+        if let (Some(block), Some(title)) = (current_block.as_mut(), window_title) {
+            block.window_title = Some(title);
+        }
+
+        // Save the open block up to now as well, so goals and reports queried
+        // mid-day see current progress. Rewriting the day file is only worth
+        // it every so often while nothing but the open block changes.
+        let day_blocks = config
+            .calendar
+            .label(&with_open_block(&time_blocks, current_block.as_ref(), now));
+        let save_due = last_save.is_none_or(|(day, finished, saved_at)| {
+            day != current_day || finished != time_blocks.len() || saved_at.elapsed() >= OPEN_BLOCK_SAVE_INTERVAL
+        });
+        if save_due {
+            store_day_blocks(&config, current_day, &day_blocks);
+            last_save = Some((current_day, time_blocks.len(), time::Instant::now()));
+        }
+        // Goals and notifications see the day as reports do, with the
+        // corrections applied
+        let effective_blocks = match corrections::load(&config.data_directory, current_day) {
+            Ok(corrections) => corrections::apply(day_blocks.clone(), &corrections),
+            Err(e) => {
+                println!("{}", e);
+                day_blocks.clone()
+            }
+        };
+        notifier.check(&config, current_day, &effective_blocks, now);
+        control_state.check_focus(&config, &effective_blocks, now, &mut notifier);
+
+        // The open block is the last one, possibly split at a meeting
         {
-            generate_daily_summary(&time_blocks, &config);
-            time_blocks.clear();
+            let mut state = api_state.lock().unwrap();
+            state.date = current_day;
+            state.current_block = current_block.as_ref().and(day_blocks.last()).cloned();
+            state.updated_at = Some(now);
+            state.paused = false;
         }
 
-        // Sleep until next interval
-        thread::sleep(time::Duration::from_secs(
-            config.screenshot_interval_seconds,
-        ));
+        // Sleep until next interval, waking early for shutdown, a config
+        // reload or a control command
+        let wake_at =
+            time::Instant::now() + time::Duration::from_secs(config.screenshot_interval_seconds);
+        while time::Instant::now() < wake_at
+            && running.load(Ordering::SeqCst)
+            && !config_watcher.reload_pending()
+            && !control_socket.as_mut().is_some_and(ControlSocket::poll)
+        {
+            thread::sleep(time::Duration::from_millis(250));
+        }
     }
 
     // Graceful shutdown
     println!("Performing graceful shutdown...");
+    drop(control_socket.take());
 
     // Finalize the current time block if it exists
+    let now = Local::now();
+    finish_elapsed_days(
+        &config,
+        now,
+        &mut current_day,
+        &mut time_blocks,
+        &mut current_block,
+    );
     if let Some(mut block) = current_block.take() {
-        block.end_time = Local::now();
+        block.end_time = now;
         time_blocks.push(block);
+        save_day_blocks(&config, current_day, &time_blocks);
     }
 
     // Generate a report for the current day
-    if !time_blocks.is_empty() {
+    let summary = if time_blocks.is_empty() {
+        None
+    } else {
+        regenerate_day(&config, current_day).unwrap_or_else(|e| {
+            println!("Failed to generate daily summary: {}", e);
+            None
+        })
+    };
+    if let Some(summary) = summary {
         println!("Generating report for today's data...");
-        let summary = generate_daily_summary(&time_blocks, &config);
 
         // Get the absolute path to the report file
         let report_path = Path::new(&config.data_directory)
@@ -274,99 +630,314 @@ fn main() {
     println!("TimeSense has been shut down gracefully.");
 }
 
-fn categorize_activity(app_name: &str, config: &Config) -> String {
-    let lower_app = app_name.to_lowercase();
+/// Loads the config from disk and swaps it in if valid. An invalid file is
+/// rejected and the current config stays active. Returns true if the config
+/// was replaced.
+fn reload_config(config: &mut Config, config_path: &Path) -> bool {
+    println!("Reloading config from {}...", config_path.display());
+
+    let new_config = match config::load(config_path) {
+        Ok(new_config) => new_config,
+        Err(e) => {
+            println!("Config reload rejected, keeping the current config: {}", e);
+            return false;
+        }
+    };
 
-    if config
-        .productive_apps
-        .iter()
-        .any(|app| lower_app.contains(app))
-    {
-        "productive".to_string()
-    } else if config
-        .distraction_apps
+    let changes = config::describe_changes(config, &new_config);
+    if changes.is_empty() {
+        println!("Config reloaded: no changes");
+        return false;
+    }
+
+    if let Err(e) = fs::create_dir_all(Path::new(&new_config.data_directory).join("screenshots")) {
+        println!("Config reload rejected, cannot create data directory: {}", e);
+        return false;
+    }
+
+    println!("Config reloaded with {} change(s):", changes.len());
+    for change in &changes {
+        println!("  {}", change);
+    }
+
+    *config = new_config;
+    true
+}
+
+/// Closes every day that ended before `now`: the open block is cut at the day
+/// boundary, the finished day is saved and its summary and report generated,
+/// and tracking continues on the next day with the remainder of the block.
+fn finish_elapsed_days(
+    config: &Config,
+    now: DateTime<Local>,
+    current_day: &mut NaiveDate,
+    time_blocks: &mut Vec<TimeBlock>,
+    current_block: &mut Option<TimeBlock>,
+) {
+    while *current_day < day_boundary::logical_date(now, config.day_start) {
+        let next_day = *current_day + Duration::days(1);
+        let boundary = day_boundary::start_of_day(next_day, config.day_start);
+
+        if let Some(block) = current_block.as_mut() {
+            let mut finished_block = block.clone();
+            finished_block.end_time = boundary;
+            time_blocks.push(finished_block);
+            block.start_time = boundary;
+        }
+
+        if !time_blocks.is_empty() {
+            save_day_blocks(config, *current_day, time_blocks);
+            println!("Day {} finished, generating its report...", current_day);
+            if let Err(e) = regenerate_day(config, *current_day) {
+                println!("Failed to generate daily summary: {}", e);
+            }
+            time_blocks.clear();
+        }
+
+        *current_day = next_day;
+    }
+}
+
+/// The finished blocks plus the open one, cut off at `now`
+fn with_open_block(time_blocks: &[TimeBlock], current_block: Option<&TimeBlock>, now: DateTime<Local>) -> Vec<TimeBlock> {
+    let mut blocks = time_blocks.to_vec();
+    if let Some(block) = current_block.filter(|block| block.start_time < now) {
+        let mut snapshot = block.clone();
+        snapshot.end_time = now;
+        blocks.push(snapshot);
+    }
+    blocks
+}
+
+/// Persists the raw blocks of a day, logging (rather than aborting on)
+/// failure. Blocks are labeled with calendar meetings first; the labeled
+/// blocks are returned.
+fn save_day_blocks(config: &Config, date: NaiveDate, time_blocks: &[TimeBlock]) -> Vec<TimeBlock> {
+    let labeled = config.calendar.label(time_blocks);
+    store_day_blocks(config, date, &labeled);
+    labeled
+}
+
+/// Persists already labeled blocks of a day, logging failure
+fn store_day_blocks(config: &Config, date: NaiveDate, labeled: &[TimeBlock]) {
+    if let Err(e) = storage::save_blocks(&config.data_directory, date, labeled) {
+        println!("Failed to save time blocks: {}", e);
+    }
+}
+
+fn categorize_activity(app_name: &str, project: Option<&str>, config: &Config) -> String {
+    config
+        .rules
         .iter()
-        .any(|app| lower_app.contains(app))
-    {
-        "distraction".to_string()
-    } else {
-        "neutral".to_string()
+        .find(|rule| rule.matches(app_name, project))
+        .map(|rule| rule.category.clone())
+        .unwrap_or_else(|| "neutral".to_string())
+}
+
+/// Rebuilds the summary and report of a stored day from its blocks, with
+/// corrections applied. Returns `None` if nothing was recorded that day.
+fn regenerate_day(config: &Config, date: NaiveDate) -> Result<Option<DailySummary>, String> {
+    let blocks = storage::load_effective_blocks(&config.data_directory, date)?;
+    if blocks.is_empty() {
+        return Ok(None);
     }
+    Ok(Some(generate_daily_summary(&blocks, date, config)))
 }
 
-fn generate_daily_summary(time_blocks: &[TimeBlock], config: &Config) -> DailySummary {
+/// Aggregates a day's blocks. Every block lands in exactly one of the
+/// productive, distracted, idle and neutral totals, so they always add up to
+/// `tracked_time`.
+fn build_daily_summary(time_blocks: &[TimeBlock], date: NaiveDate, focus_tolerance: Duration) -> DailySummary {
     let mut productive_time = Duration::zero();
     let mut distracted_time = Duration::zero();
     let mut idle_time = Duration::zero();
+    let mut neutral_time = Duration::zero();
+    let mut tracked_time = Duration::zero();
     let mut app_breakdown: HashMap<String, Duration> = HashMap::new();
     let mut activity_breakdown: HashMap<String, Duration> = HashMap::new();
+    let mut category_breakdown: HashMap<String, Duration> = HashMap::new();
+    let mut project_breakdown: HashMap<String, Duration> = HashMap::new();
 
     for block in time_blocks {
         let duration = block.end_time.signed_duration_since(block.start_time);
+        tracked_time += duration;
 
         // Update application breakdown
         let app_duration = app_breakdown
             .entry(block.application.clone())
             .or_insert(Duration::zero());
-        *app_duration = *app_duration + duration;
+        *app_duration += duration;
+
+        // Update project breakdown
+        if let Some(project) = &block.project {
+            let project_duration = project_breakdown
+                .entry(project.clone())
+                .or_insert(Duration::zero());
+            *project_duration += duration;
+        }
 
         // Update activity breakdown
         let activity_duration = activity_breakdown
             .entry(block.activity_type.clone())
             .or_insert(Duration::zero());
-        *activity_duration = *activity_duration + duration;
+        *activity_duration += duration;
 
         // Update time categories
         if block.idle {
-            idle_time = idle_time + duration;
-        } else if block.activity_type == "productive" {
-            productive_time = productive_time + duration;
+            idle_time += duration;
+            continue;
+        }
+        *category_breakdown
+            .entry(block.activity_type.clone())
+            .or_insert(Duration::zero()) += duration;
+        if block.activity_type == "productive" {
+            productive_time += duration;
         } else if block.activity_type == "distraction" {
-            distracted_time = distracted_time + duration;
+            distracted_time += duration;
+        } else {
+            neutral_time += duration;
         }
     }
 
-    let date = time_blocks
-        .first()
-        .unwrap()
-        .start_time
-        .date_naive()
-        .format("%Y-%m-%d")
-        .to_string();
+    debug_assert_eq!(
+        productive_time + distracted_time + idle_time + neutral_time,
+        tracked_time
+    );
 
-    let summary = DailySummary {
-        date,
+    DailySummary {
+        date: date.format("%Y-%m-%d").to_string(),
         productive_time,
         distracted_time,
         idle_time,
+        neutral_time,
+        tracked_time,
         application_breakdown: app_breakdown,
         activity_breakdown,
-    };
+        category_breakdown,
+        project_breakdown,
+        focus_metrics: FocusMetrics::from_blocks(time_blocks, focus_tolerance),
+    }
+}
+
+fn generate_daily_summary(time_blocks: &[TimeBlock], date: NaiveDate, config: &Config) -> DailySummary {
+    let summary = build_daily_summary(time_blocks, date, config.focus_tolerance());
 
     // Save the summary to disk
     let filename = format!("{}/summary_{}.json", config.data_directory, summary.date);
     let json = serde_json::to_string_pretty(&summary).unwrap();
     fs::write(filename, json).expect("Failed to write daily summary");
 
+    let goal_progress = goals::progress(config, date, time_blocks, Local::now()).unwrap_or_else(|e| {
+        println!("Failed to evaluate goals: {}", e);
+        Vec::new()
+    });
+    let meetings = config.calendar.meetings(date, config.day_start, time_blocks);
+
     // Generate report using the ReportGenerator
-    let report_generator = ReportGenerator::new(config.data_directory.clone());
-    report_generator.generate_report(&summary);
+    let report_generator = ReportGenerator::new(
+        config.data_directory.clone(),
+        config.scoring.clone(),
+        config.reports.clone(),
+    );
+    report_generator.generate_report(&summary, time_blocks, &goal_progress, &meetings);
 
     summary
 }
 
-fn load_config() -> Option<Config> {
-    let config_path = "timesense_config.json";
-    if Path::new(config_path).exists() {
-        let contents = fs::read_to_string(config_path).ok()?;
-        serde_json::from_str(&contents).ok()
-    } else {
-        None
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_format::{format_duration, percentage};
+    use chrono::TimeZone;
+
+    fn block(start: DateTime<Local>, millis: i64, activity_type: &str, idle: bool) -> TimeBlock {
+        let mut block = TimeBlock::new(start, "editor".to_string(), activity_type.to_string(), idle);
+        block.end_time = start + Duration::milliseconds(millis);
+        block
     }
-}
 
-fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
-    let json = serde_json::to_string_pretty(config)?;
-    fs::write("timesense_config.json", json)?;
-    Ok(())
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+    }
+
+    #[test]
+    fn sub_minute_blocks_add_up_exactly() {
+        let start = Local.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let blocks: Vec<TimeBlock> = (0..100)
+            .map(|index| block(start + Duration::milliseconds(1500 * index), 1500, "productive", false))
+            .collect();
+        let summary = build_daily_summary(&blocks, date(), Duration::zero());
+        assert_eq!(summary.productive_time, Duration::seconds(150));
+        assert_eq!(summary.tracked_time, Duration::seconds(150));
+        assert_eq!(summary.application_breakdown["editor"], Duration::seconds(150));
+        assert_eq!(format_duration(summary.tracked_time), "2m 30s");
+    }
+
+    #[test]
+    fn every_block_lands_in_one_total() {
+        let start = Local.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let blocks = vec![
+            block(start, 60_000, "productive", false),
+            block(start + Duration::minutes(1), 30_000, "distraction", false),
+            block(start + Duration::minutes(2), 45_000, "neutral", false),
+            block(start + Duration::minutes(3), 20_000, "meeting", false),
+            block(start + Duration::minutes(4), 90_000, "productive", true),
+        ];
+        let summary = build_daily_summary(&blocks, date(), Duration::zero());
+        assert_eq!(summary.productive_time, Duration::seconds(60));
+        assert_eq!(summary.distracted_time, Duration::seconds(30));
+        assert_eq!(summary.neutral_time, Duration::seconds(65));
+        assert_eq!(summary.idle_time, Duration::seconds(90));
+        assert_eq!(
+            summary.productive_time + summary.distracted_time + summary.neutral_time + summary.idle_time,
+            summary.tracked_time
+        );
+        assert_eq!(summary.tracked_time, Duration::seconds(245));
+        // Idle time is in the activity breakdown but not the categories
+        assert_eq!(summary.activity_breakdown["productive"], Duration::seconds(150));
+        assert_eq!(summary.category_breakdown["productive"], Duration::seconds(60));
+        assert_eq!(summary.category_breakdown["meeting"], Duration::seconds(20));
+    }
+
+    #[test]
+    fn empty_days_have_no_time_and_no_shares() {
+        let summary = build_daily_summary(&[], date(), Duration::zero());
+        assert_eq!(summary.tracked_time, Duration::zero());
+        assert_eq!(percentage(summary.productive_time, summary.tracked_time), 0.0);
+        assert_eq!(format_duration(summary.tracked_time), "0s");
+    }
+
+    #[test]
+    fn an_invalid_config_keeps_the_current_one() {
+        let dir = std::env::temp_dir().join(format!("timesense-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("timesense_config.json");
+        let mut config = Config {
+            data_directory: dir.join("data").to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+        config::save(&config, &path).unwrap();
+
+        // Unchanged, unparsable and invalid files are not swapped in
+        assert!(!reload_config(&mut config, &path));
+        fs::write(&path, "{ not json").unwrap();
+        assert!(!reload_config(&mut config, &path));
+        let invalid = Config {
+            screenshot_interval_seconds: 0,
+            ..config.clone()
+        };
+        config::save(&invalid, &path).unwrap();
+        assert!(!reload_config(&mut config, &path));
+        assert_eq!(config.screenshot_interval_seconds, Config::default().screenshot_interval_seconds);
+
+        let changed = Config {
+            screenshot_interval_seconds: 42,
+            ..config.clone()
+        };
+        config::save(&changed, &path).unwrap();
+        assert!(reload_config(&mut config, &path));
+        assert_eq!(config.screenshot_interval_seconds, 42);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,250 @@
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::TimeBlock;
+
+/// How fragmented a day was, derived from the order of its blocks rather than
+/// from category totals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FocusMetrics {
+    /// Changes of application between consecutive active blocks
+    pub app_switches: usize,
+    /// `app_switches` per hour of active (non-idle) time
+    pub switches_per_hour: f64,
+    /// Median length of runs of productive blocks with nothing in between
+    pub median_focus_stretch: Option<Duration>,
+    /// Longest productive session, allowing interruptions up to the
+    /// configured tolerance
+    pub longest_deep_work: Option<DeepWorkSession>,
+    /// Median time from the end of a distraction to the next productive block
+    pub median_return_to_focus: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepWorkSession {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    /// Productive time within the session, excluding the interruptions
+    pub focused_time: Duration,
+}
+
+impl FocusMetrics {
+    /// Computes the metrics for one day. Interruptions (other blocks or gaps
+    /// in tracking) no longer than `tolerance` don't end a deep-work session.
+    pub fn from_blocks(blocks: &[TimeBlock], tolerance: Duration) -> Self {
+        let mut blocks: Vec<&TimeBlock> = blocks.iter().collect();
+        blocks.sort_by_key(|block| block.start_time);
+
+        let active: Vec<&TimeBlock> = blocks.iter().copied().filter(|block| !block.idle).collect();
+        let app_switches = active
+            .windows(2)
+            .filter(|pair| pair[0].application != pair[1].application)
+            .count();
+        let active_time = active.iter().fold(Duration::zero(), |total, block| total + duration(block));
+        let switches_per_hour = if active_time > Duration::zero() {
+            app_switches as f64 / (active_time.num_milliseconds() as f64 / 3_600_000.0)
+        } else {
+            0.0
+        };
+
+        FocusMetrics {
+            app_switches,
+            switches_per_hour,
+            median_focus_stretch: median(focus_stretches(&blocks)),
+            longest_deep_work: longest_deep_work(&blocks, tolerance),
+            median_return_to_focus: median(returns_to_focus(&blocks)),
+        }
+    }
+}
+
+fn is_focus(block: &TimeBlock) -> bool {
+    !block.idle && block.activity_type == "productive"
+}
+
+fn is_distraction(block: &TimeBlock) -> bool {
+    !block.idle && block.activity_type == "distraction"
+}
+
+fn duration(block: &TimeBlock) -> Duration {
+    block.end_time.signed_duration_since(block.start_time)
+}
+
+/// Lengths of runs of back-to-back productive blocks
+fn focus_stretches(blocks: &[&TimeBlock]) -> Vec<Duration> {
+    let mut stretches = Vec::new();
+    let mut current: Option<(DateTime<Local>, DateTime<Local>)> = None;
+
+    for block in blocks {
+        current = match current {
+            Some((start, end)) if is_focus(block) && block.start_time <= end => {
+                Some((start, end.max(block.end_time)))
+            }
+            _ => {
+                if let Some((start, end)) = current {
+                    stretches.push(end.signed_duration_since(start));
+                }
+                is_focus(block).then_some((block.start_time, block.end_time))
+            }
+        };
+    }
+    if let Some((start, end)) = current {
+        stretches.push(end.signed_duration_since(start));
+    }
+    stretches
+}
+
+fn longest_deep_work(blocks: &[&TimeBlock], tolerance: Duration) -> Option<DeepWorkSession> {
+    let mut longest: Option<DeepWorkSession> = None;
+    let mut current: Option<DeepWorkSession> = None;
+
+    for block in blocks.iter().filter(|block| is_focus(block)) {
+        current = match current.take() {
+            Some(mut session) if block.start_time.signed_duration_since(session.end) <= tolerance => {
+                session.end = session.end.max(block.end_time);
+                session.focused_time += duration(block);
+                Some(session)
+            }
+            finished => {
+                keep_longer(&mut longest, finished);
+                Some(DeepWorkSession {
+                    start: block.start_time,
+                    end: block.end_time,
+                    focused_time: duration(block),
+                })
+            }
+        };
+    }
+    keep_longer(&mut longest, current);
+    longest
+}
+
+fn keep_longer(longest: &mut Option<DeepWorkSession>, candidate: Option<DeepWorkSession>) {
+    match (longest.as_ref(), candidate) {
+        (Some(session), Some(candidate)) if candidate.focused_time <= session.focused_time => {}
+        (_, Some(candidate)) => *longest = Some(candidate),
+        (_, None) => {}
+    }
+}
+
+/// For every distraction that is followed by productive work later in the day,
+/// the time from the end of the distraction until that work started
+fn returns_to_focus(blocks: &[&TimeBlock]) -> Vec<Duration> {
+    let mut returns = Vec::new();
+    let mut distracted_until: Option<DateTime<Local>> = None;
+
+    for block in blocks {
+        if is_distraction(block) {
+            distracted_until = Some(block.end_time);
+        } else if is_focus(block) {
+            if let Some(end) = distracted_until.take() {
+                returns.push(block.start_time.signed_duration_since(end).max(Duration::zero()));
+            }
+        }
+    }
+    returns
+}
+
+fn median(mut values: Vec<Duration>) -> Option<Duration> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    let middle = values.len() / 2;
+    match values.len() % 2 {
+        0 => Some((values[middle - 1] + values[middle]) / 2),
+        _ => Some(values[middle]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 19, hour, minute, 0).unwrap()
+    }
+
+    fn block(start: DateTime<Local>, end: DateTime<Local>, app: &str, activity_type: &str) -> TimeBlock {
+        let mut block = TimeBlock::new(start, app.to_string(), activity_type.to_string(), false);
+        block.end_time = end;
+        block
+    }
+
+    #[test]
+    fn an_empty_day_has_no_metrics() {
+        let metrics = FocusMetrics::from_blocks(&[], Duration::minutes(5));
+        assert_eq!(metrics.app_switches, 0);
+        assert_eq!(metrics.switches_per_hour, 0.0);
+        assert!(metrics.median_focus_stretch.is_none());
+        assert!(metrics.longest_deep_work.is_none());
+        assert!(metrics.median_return_to_focus.is_none());
+    }
+
+    #[test]
+    fn switches_and_focus_stretches() {
+        // Stretches of 30m (two back-to-back blocks), 10m and 60m
+        let blocks = [
+            block(at(9, 0), at(9, 20), "editor", "productive"),
+            block(at(9, 20), at(9, 30), "terminal", "productive"),
+            block(at(9, 30), at(9, 40), "chat", "distraction"),
+            block(at(9, 40), at(9, 50), "editor", "productive"),
+            block(at(10, 0), at(11, 0), "editor", "productive"),
+        ];
+        let metrics = FocusMetrics::from_blocks(&blocks, Duration::zero());
+        assert_eq!(metrics.app_switches, 3);
+        assert_eq!(metrics.switches_per_hour, 3.0 / (110.0 / 60.0));
+        assert_eq!(metrics.median_focus_stretch, Some(Duration::minutes(30)));
+
+        // An even number of stretches averages the middle two
+        let metrics = FocusMetrics::from_blocks(&blocks[..4], Duration::zero());
+        assert_eq!(metrics.median_focus_stretch, Some(Duration::minutes(20)));
+    }
+
+    #[test]
+    fn interruptions_up_to_the_tolerance_keep_deep_work_going() {
+        let blocks = [
+            block(at(9, 0), at(9, 40), "editor", "productive"),
+            block(at(9, 40), at(9, 45), "chat", "distraction"),
+            block(at(9, 45), at(10, 15), "editor", "productive"),
+            block(at(10, 15), at(10, 21), "chat", "distraction"),
+            block(at(10, 21), at(10, 51), "editor", "productive"),
+        ];
+
+        // The 5 minute interruption is exactly at the tolerance, the 6
+        // minute one is past it
+        let session = FocusMetrics::from_blocks(&blocks, Duration::minutes(5)).longest_deep_work.unwrap();
+        assert_eq!((session.start, session.end), (at(9, 0), at(10, 15)));
+        assert_eq!(session.focused_time, Duration::minutes(70));
+
+        let session = FocusMetrics::from_blocks(&blocks, Duration::minutes(6)).longest_deep_work.unwrap();
+        assert_eq!((session.start, session.end), (at(9, 0), at(10, 51)));
+        assert_eq!(session.focused_time, Duration::minutes(100));
+
+        let session = FocusMetrics::from_blocks(&blocks, Duration::minutes(4)).longest_deep_work.unwrap();
+        assert_eq!((session.start, session.end), (at(9, 0), at(9, 40)));
+    }
+
+    #[test]
+    fn return_to_focus_is_measured_from_the_end_of_a_distraction() {
+        let mut idle = block(at(9, 40), at(9, 50), "editor", "productive");
+        idle.idle = true;
+        let blocks = [
+            block(at(9, 0), at(9, 30), "editor", "productive"),
+            block(at(9, 30), at(9, 40), "chat", "distraction"),
+            idle,
+            block(at(9, 50), at(10, 0), "mail", "neutral"),
+            block(at(10, 0), at(10, 30), "editor", "productive"),
+            block(at(10, 30), at(10, 35), "chat", "distraction"),
+            block(at(10, 37), at(11, 0), "editor", "productive"),
+            block(at(11, 0), at(11, 15), "video", "distraction"),
+        ];
+
+        // 20m and 2m; the last distraction has no focus after it
+        let metrics = FocusMetrics::from_blocks(&blocks, Duration::zero());
+        assert_eq!(metrics.median_return_to_focus, Some(Duration::minutes(11)));
+
+        let metrics = FocusMetrics::from_blocks(&blocks[7..], Duration::zero());
+        assert!(metrics.median_return_to_focus.is_none());
+    }
+}
@@ -0,0 +1,595 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+#[cfg(target_os = "linux")]
+use std::sync::mpsc;
+#[cfg(target_os = "linux")]
+use std::thread;
+#[cfg(target_os = "linux")]
+use std::time;
+
+use crate::config::Config;
+use crate::goals::{GoalKind, GoalState};
+use crate::time_format::format_duration;
+use crate::TimeBlock;
+
+/// Longest wait for the notification service, so a hung service can't stall
+/// the tracker loop
+#[cfg(target_os = "linux")]
+const SEND_TIMEOUT: time::Duration = time::Duration::from_secs(2);
+
+/// When the tracker raises desktop notifications
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub enabled: bool,
+    /// Notify when an `at_most` goal is exceeded
+    pub budget_exceeded: bool,
+    /// Notify when an `at_least` goal is reached
+    pub goal_reached: bool,
+    /// Notify once distraction has lasted this long without a break
+    pub distraction_streak_minutes: Option<u64>,
+    /// Minimum time between two notifications; anything due in between is
+    /// sent afterwards if it still applies
+    pub min_interval_minutes: u64,
+    /// No notifications between these times (may span midnight)
+    pub quiet_hours: Option<QuietHours>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        NotificationConfig {
+            enabled: false,
+            budget_exceeded: true,
+            goal_reached: true,
+            distraction_streak_minutes: Some(20),
+            min_interval_minutes: 10,
+            quiet_hours: None,
+        }
+    }
+}
+
+impl QuietHours {
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Raises notifications from the tracker loop. Each event is sent at most
+/// once: budgets and goals once per day, distraction streaks once per streak.
+pub struct Notifier {
+    #[cfg(target_os = "linux")]
+    connection: Option<zbus::blocking::Connection>,
+    /// D-Bus address to use instead of the session bus
+    #[cfg(target_os = "linux")]
+    bus_address: Option<String>,
+    last_sent: Option<DateTime<Local>>,
+    /// Day the keys in `sent` belong to
+    day: Option<NaiveDate>,
+    /// Keys of events already sent
+    sent: HashSet<String>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Notifier {
+            #[cfg(target_os = "linux")]
+            connection: None,
+            #[cfg(target_os = "linux")]
+            bus_address: None,
+            last_sent: None,
+            day: None,
+            sent: HashSet::new(),
+        }
+    }
+
+    /// Checks the day's blocks (including the open one) for events and sends
+    /// the first one that is due
+    pub fn check(&mut self, config: &Config, date: NaiveDate, blocks: &[TimeBlock], now: DateTime<Local>) {
+        let settings = &config.notifications;
+        if !settings.enabled {
+            return;
+        }
+        // Events are per day, so the keys of earlier days can go
+        if self.day != Some(date) {
+            self.day = Some(date);
+            self.sent.clear();
+        }
+        if let Some(quiet_hours) = &settings.quiet_hours {
+            if quiet_hours.contains(now.time()) {
+                return;
+            }
+        }
+        if let Some(last_sent) = self.last_sent {
+            if now.signed_duration_since(last_sent) < Duration::minutes(settings.min_interval_minutes as i64) {
+                return;
+            }
+        }
+
+        let event = pending_events(config, date, blocks, now)
+            .into_iter()
+            .find(|(key, _, _)| !self.sent.contains(key));
+        if let Some((key, summary, body)) = event {
+            // Counted as sent even if delivery fails, so a missing
+            // notification daemon doesn't cause a retry every sample
+            self.last_sent = Some(now);
+            self.sent.insert(key);
+            if let Err(e) = self.send(&summary, &body) {
+                println!("{}", e);
+            }
+        }
+    }
+
+    /// Sends through the bus at `address` instead of the session bus
+    #[cfg(all(test, target_os = "linux"))]
+    fn with_bus_address(address: &str) -> Self {
+        Notifier {
+            bus_address: Some(address.to_string()),
+            ..Notifier::new()
+        }
+    }
+
+    /// Delivers the notification on a separate thread and gives up after
+    /// `SEND_TIMEOUT`; a call still hanging then finishes in the background
+    #[cfg(target_os = "linux")]
+    pub fn send(&mut self, summary: &str, body: &str) -> Result<(), String> {
+        let connection = self.connection.take();
+        let bus_address = self.bus_address.clone();
+        let notification = (summary.to_string(), body.to_string());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (summary, body) = notification;
+            let _ = sender.send(deliver(connection, bus_address.as_deref(), &summary, &body));
+        });
+
+        match receiver.recv_timeout(SEND_TIMEOUT) {
+            Ok(Ok(connection)) => {
+                self.connection = Some(connection);
+                println!("Notification sent: {}", summary);
+                Ok(())
+            }
+            Ok(Err(e)) => Err(e),
+            // The connection stays with the hanging call; the next send
+            // opens a new one
+            Err(_) => Err(format!(
+                "The notification service did not answer within {} seconds",
+                SEND_TIMEOUT.as_secs()
+            )),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn send(&mut self, summary: &str, body: &str) -> Result<(), String> {
+        println!("Notification: {} - {}", summary, body);
+        Ok(())
+    }
+}
+
+/// Calls `Notify` on the notification service, connecting first if needed,
+/// and returns the connection for reuse
+#[cfg(target_os = "linux")]
+fn deliver(
+    connection: Option<zbus::blocking::Connection>,
+    bus_address: Option<&str>,
+    summary: &str,
+    body: &str,
+) -> Result<zbus::blocking::Connection, String> {
+    use std::collections::HashMap;
+    use zbus::zvariant::Value;
+
+    let connection = match (connection, bus_address) {
+        (Some(connection), _) => connection,
+        (None, None) => zbus::blocking::Connection::session()
+            .map_err(|e| format!("Failed to connect to the session bus: {}", e))?,
+        (None, Some(address)) => zbus::blocking::connection::Builder::address(address)
+            .and_then(|builder| builder.build())
+            .map_err(|e| format!("Failed to connect to {}: {}", address, e))?,
+    };
+
+    let actions: Vec<&str> = Vec::new();
+    let hints: HashMap<&str, Value> = HashMap::new();
+    connection
+        .call_method(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            Some("org.freedesktop.Notifications"),
+            "Notify",
+            // app name, replaces id, icon, summary, body, actions, hints, timeout
+            &("TimeSense", 0u32, "", summary, body, actions, hints, -1i32),
+        )
+        .map_err(|e| format!("Failed to send notification: {}", e))?;
+    Ok(connection)
+}
+
+/// Every event that currently applies, as (key, summary, body)
+fn pending_events(config: &Config, date: NaiveDate, blocks: &[TimeBlock], now: DateTime<Local>) -> Vec<(String, String, String)> {
+    let settings = &config.notifications;
+    let mut events = Vec::new();
+
+    if let Some(minutes) = settings.distraction_streak_minutes {
+        if let Some((start, length)) = distraction_streak(blocks) {
+            if length >= Duration::minutes(minutes as i64) {
+                events.push((
+                    format!("streak:{}", start.to_rfc3339()),
+                    "Time for a break from distractions".to_string(),
+                    format!(
+                        "You've been on distracting apps for {} (since {})",
+                        format_duration(length),
+                        start.format("%H:%M")
+                    ),
+                ));
+            }
+        }
+    }
+
+    for goal in config.goals.iter().filter(|goal| goal.applies_on(date)) {
+        let (state, actual, _) = goal.evaluate(blocks, date, config.day_start, now);
+        let (enabled, summary) = match (goal.kind, state) {
+            (GoalKind::AtMost, GoalState::Missed) => (settings.budget_exceeded, "Budget exceeded"),
+            (GoalKind::AtLeast, GoalState::Met) => (settings.goal_reached, "Goal reached"),
+            _ => continue,
+        };
+        if enabled {
+            events.push((
+                format!("goal:{}:{}", date, goal.name),
+                format!("{}: {}", summary, goal.name),
+                format!("{} so far today ({})", format_duration(actual), goal.describe()),
+            ));
+        }
+    }
+
+    events
+}
+
+/// Start and length of the distraction running up to the last block, if the
+/// day currently ends in one. Consecutive distracting apps count as one streak.
+fn distraction_streak(blocks: &[TimeBlock]) -> Option<(DateTime<Local>, Duration)> {
+    let is_distraction = |block: &&TimeBlock| !block.idle && block.activity_type == "distraction";
+    let mut streak = blocks.iter().rev().take_while(is_distraction);
+    let last = streak.next()?;
+    let first = streak.last().unwrap_or(last);
+    Some((first.start_time, last.end_time.signed_duration_since(first.start_time)))
+}
+
+/// Sends a test notification, ignoring quiet hours and rate limits
+pub fn run_test() -> Result<(), String> {
+    Notifier::new().send(
+        "TimeSense notifications work",
+        "Budget, goal and distraction notifications will appear like this.",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goals::Goal;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, day, hour, minute, 0).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn block(start: DateTime<Local>, end: DateTime<Local>, activity_type: &str) -> TimeBlock {
+        let mut block = TimeBlock::new(start, "app".to_string(), activity_type.to_string(), false);
+        block.end_time = end;
+        block
+    }
+
+    fn goal(name: &str, kind: GoalKind, category: &str, minutes: u64) -> Goal {
+        Goal {
+            name: name.to_string(),
+            kind,
+            category: Some(category.to_string()),
+            app: None,
+            minutes: Some(minutes),
+            time: None,
+            days: Vec::new(),
+        }
+    }
+
+    fn notifying_config(quiet_hours: Option<QuietHours>) -> Config {
+        Config {
+            notifications: NotificationConfig {
+                enabled: true,
+                distraction_streak_minutes: Some(20),
+                min_interval_minutes: 10,
+                quiet_hours,
+                ..NotificationConfig::default()
+            },
+            ..Config::default()
+        }
+    }
+
+    /// A notifier whose deliveries fail right away, so `check` can be tested
+    /// without a notification service
+    fn offline_notifier() -> Notifier {
+        #[cfg(target_os = "linux")]
+        return Notifier::with_bus_address("unix:path=/nonexistent/timesense-test-bus");
+        #[cfg(not(target_os = "linux"))]
+        return Notifier::new();
+    }
+
+    #[test]
+    fn quiet_hours_may_span_midnight() {
+        let day = QuietHours { start: time(12, 0), end: time(13, 0) };
+        assert!(day.contains(time(12, 0)));
+        assert!(day.contains(time(12, 59)));
+        assert!(!day.contains(time(13, 0)));
+        assert!(!day.contains(time(11, 59)));
+
+        let night = QuietHours { start: time(22, 0), end: time(7, 0) };
+        assert!(night.contains(time(22, 0)));
+        assert!(night.contains(time(23, 59)));
+        assert!(night.contains(time(0, 0)));
+        assert!(night.contains(time(6, 59)));
+        assert!(!night.contains(time(7, 0)));
+        assert!(!night.contains(time(21, 59)));
+    }
+
+    #[test]
+    fn streaks_run_up_to_the_last_block() {
+        assert_eq!(distraction_streak(&[]), None);
+
+        let blocks = [
+            block(at(19, 9, 0), at(19, 9, 30), "distraction"),
+            block(at(19, 9, 30), at(19, 9, 40), "productive"),
+            block(at(19, 9, 40), at(19, 9, 50), "distraction"),
+            block(at(19, 9, 50), at(19, 10, 5), "distraction"),
+        ];
+        assert_eq!(distraction_streak(&blocks), Some((at(19, 9, 40), Duration::minutes(25))));
+        assert_eq!(distraction_streak(&blocks[..2]), None);
+
+        let mut idle = block(at(19, 10, 5), at(19, 10, 10), "distraction");
+        idle.idle = true;
+        assert_eq!(distraction_streak(&[blocks[3].clone(), idle]), None);
+    }
+
+    #[test]
+    fn pending_events_cover_streaks_budgets_and_goals() {
+        let mut config = notifying_config(None);
+        config.goals = vec![
+            goal("chat", GoalKind::AtMost, "distraction", 20),
+            goal("deep work", GoalKind::AtLeast, "productive", 60),
+        ];
+        let date = at(19, 0, 0).date_naive();
+        let blocks = [
+            block(at(19, 8, 0), at(19, 9, 0), "productive"),
+            block(at(19, 9, 0), at(19, 9, 25), "distraction"),
+        ];
+
+        let events = pending_events(&config, date, &blocks, at(19, 9, 25));
+        let summaries: Vec<&str> = events.iter().map(|(_, summary, _)| summary.as_str()).collect();
+        assert_eq!(
+            summaries,
+            vec!["Time for a break from distractions", "Budget exceeded: chat", "Goal reached: deep work"]
+        );
+        assert_eq!(events[0].0, format!("streak:{}", at(19, 9, 0).to_rfc3339()));
+
+        // A streak below the threshold and switched-off goal events
+        config.notifications.budget_exceeded = false;
+        config.notifications.goal_reached = false;
+        let short = [blocks[0].clone(), block(at(19, 9, 0), at(19, 9, 15), "distraction")];
+        let events = pending_events(&config, date, &short, at(19, 9, 15));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn notifications_are_rate_limited_and_held_back_in_quiet_hours() {
+        let date = at(19, 0, 0).date_naive();
+        let config = notifying_config(None);
+        let streak = [block(at(19, 9, 0), at(19, 9, 20), "distraction")];
+
+        // A streak is announced once
+        let mut notifier = offline_notifier();
+        notifier.check(&config, date, &streak, at(19, 9, 20));
+        assert_eq!(notifier.sent.len(), 1);
+        assert_eq!(notifier.last_sent, Some(at(19, 9, 20)));
+        notifier.check(&config, date, &streak, at(19, 9, 40));
+        assert_eq!(notifier.last_sent, Some(at(19, 9, 20)));
+
+        // A new streak within min_interval_minutes waits for the interval
+        let second = [
+            block(at(19, 9, 0), at(19, 9, 20), "distraction"),
+            block(at(19, 9, 20), at(19, 9, 21), "productive"),
+            block(at(19, 9, 21), at(19, 9, 41), "distraction"),
+        ];
+        let mut notifier = offline_notifier();
+        notifier.check(&config, date, &streak, at(19, 9, 35));
+        notifier.check(&config, date, &second, at(19, 9, 41));
+        assert_eq!(notifier.sent.len(), 1);
+        notifier.check(&config, date, &second, at(19, 9, 45));
+        assert_eq!(notifier.sent.len(), 2);
+
+        // Nothing during quiet hours; due events follow after
+        let config = notifying_config(Some(QuietHours { start: time(22, 0), end: time(8, 0) }));
+        let night = [block(at(19, 7, 0), at(19, 7, 30), "distraction")];
+        let mut notifier = offline_notifier();
+        notifier.check(&config, date, &night, at(19, 7, 30));
+        assert!(notifier.sent.is_empty());
+        notifier.check(&config, date, &night, at(19, 8, 0));
+        assert_eq!(notifier.sent.len(), 1);
+
+        // Disabled means silent
+        let mut config = config;
+        config.notifications.enabled = false;
+        let mut notifier = offline_notifier();
+        notifier.check(&config, date, &streak, at(19, 9, 20));
+        assert!(notifier.sent.is_empty());
+    }
+
+    #[test]
+    fn sent_events_are_forgotten_on_a_new_day() {
+        let config = notifying_config(None);
+        let mut notifier = offline_notifier();
+        let today = [block(at(19, 9, 0), at(19, 9, 20), "distraction")];
+        notifier.check(&config, at(19, 0, 0).date_naive(), &today, at(19, 9, 20));
+        assert_eq!(notifier.sent.len(), 1);
+
+        let tomorrow = [block(at(20, 9, 0), at(20, 9, 20), "distraction")];
+        notifier.check(&config, at(20, 0, 0).date_naive(), &tomorrow, at(20, 9, 20));
+        assert_eq!(notifier.sent.len(), 1);
+        assert!(notifier.sent.iter().all(|key| key.contains("2026-10-20")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn an_unreachable_bus_is_an_error() {
+        assert!(offline_notifier().send("Nobody", "listens").is_err());
+    }
+
+    /// Sends notifications to a mock notification service on a private bus.
+    /// Needs `dbus-daemon`; run with `cargo test -- --ignored`.
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore]
+    fn notify_round_trip_on_a_private_bus() {
+        let bus = dbus::TestBus::start();
+        let (_service, calls) = dbus::serve_mock(&bus, time::Duration::ZERO);
+
+        let mut notifier = Notifier::with_bus_address(&bus.address);
+        notifier.send("Budget exceeded: chat", "1h 5m so far today").unwrap();
+        notifier.send("Second", "Reuses the connection").unwrap();
+        {
+            let calls = calls.lock().unwrap();
+            assert_eq!(
+                calls[0],
+                (
+                    "TimeSense".to_string(),
+                    0,
+                    String::new(),
+                    "Budget exceeded: chat".to_string(),
+                    "1h 5m so far today".to_string(),
+                    0,
+                    0,
+                    -1
+                )
+            );
+            assert_eq!(calls.len(), 2);
+        }
+
+        // A service that hangs is given up on
+        let slow_bus = dbus::TestBus::start();
+        let (_slow_service, _) = dbus::serve_mock(&slow_bus, time::Duration::from_secs(5));
+        let mut notifier = Notifier::with_bus_address(&slow_bus.address);
+        let started = time::Instant::now();
+        let result = notifier.send("Slow", "The service takes 5 seconds");
+        assert!(result.unwrap_err().contains("did not answer"));
+        assert!(started.elapsed() < time::Duration::from_secs(4));
+    }
+
+    /// A private bus with a mock notification service
+    #[cfg(target_os = "linux")]
+    mod dbus {
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+        use std::process::{Child, Command, Stdio};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+        use std::{thread, time};
+
+        /// A bus run by `dbus-daemon`, stopped on drop
+        pub struct TestBus {
+            daemon: Child,
+            dir: PathBuf,
+            pub address: String,
+        }
+
+        impl TestBus {
+            pub fn start() -> TestBus {
+                static STARTED: AtomicUsize = AtomicUsize::new(0);
+                let number = STARTED.fetch_add(1, Ordering::SeqCst);
+                let dir = std::env::temp_dir().join(format!("timesense-bus-{}-{}", std::process::id(), number));
+                std::fs::create_dir_all(&dir).unwrap();
+                let socket = dir.join("bus");
+                let address = format!("unix:path={}", socket.display());
+                let daemon = Command::new("dbus-daemon")
+                    .args(["--session", "--nofork", "--nopidfile"])
+                    .arg(format!("--address={}", address))
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()
+                    .expect("this test needs dbus-daemon");
+                let bus = TestBus { daemon, dir, address };
+                for _ in 0..100 {
+                    if socket.exists() {
+                        return bus;
+                    }
+                    thread::sleep(time::Duration::from_millis(50));
+                }
+                panic!("dbus-daemon did not create {}", socket.display());
+            }
+        }
+
+        impl Drop for TestBus {
+            fn drop(&mut self) {
+                let _ = self.daemon.kill();
+                let _ = self.daemon.wait();
+                let _ = std::fs::remove_dir_all(&self.dir);
+            }
+        }
+
+        /// Arguments of a `Notify` call: app name, replaces id, icon,
+        /// summary, body, number of actions, number of hints and expiry
+        pub type NotifyCall = (String, u32, String, String, String, usize, usize, i32);
+
+        /// Records `Notify` calls, answering after `delay`
+        struct MockNotifications {
+            calls: Arc<Mutex<Vec<NotifyCall>>>,
+            delay: time::Duration,
+        }
+
+        #[zbus::interface(name = "org.freedesktop.Notifications")]
+        impl MockNotifications {
+            #[allow(clippy::too_many_arguments)]
+            fn notify(
+                &self,
+                app_name: String,
+                replaces_id: u32,
+                app_icon: String,
+                summary: String,
+                body: String,
+                actions: Vec<String>,
+                hints: HashMap<String, zbus::zvariant::OwnedValue>,
+                expire_timeout: i32,
+            ) -> u32 {
+                thread::sleep(self.delay);
+                let mut calls = self.calls.lock().unwrap();
+                calls.push((app_name, replaces_id, app_icon, summary, body, actions.len(), hints.len(), expire_timeout));
+                calls.len() as u32
+            }
+        }
+
+        /// Registers the mock service on `bus`; it runs as long as the
+        /// returned connection is kept
+        pub fn serve_mock(bus: &TestBus, delay: time::Duration) -> (zbus::blocking::Connection, Arc<Mutex<Vec<NotifyCall>>>) {
+            let calls = Arc::new(Mutex::new(Vec::new()));
+            let mock = MockNotifications {
+                calls: calls.clone(),
+                delay,
+            };
+            let connection = zbus::blocking::connection::Builder::address(bus.address.as_str())
+                .unwrap()
+                .name("org.freedesktop.Notifications")
+                .unwrap()
+                .serve_at("/org/freedesktop/Notifications", mock)
+                .unwrap()
+                .build()
+                .unwrap();
+            (connection, calls)
+        }
+    }
+}
@@ -0,0 +1,734 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::day_boundary;
+use crate::goals;
+use crate::heatmap::Heatmap;
+use crate::storage;
+use crate::templates;
+use crate::time_format::{format_duration, percentage};
+use crate::{build_daily_summary, DailySummary, TimeBlock};
+
+/// Number of applications listed per week
+const TOP_APPS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodKind {
+    Week,
+    Month,
+    Range,
+}
+
+/// A span of whole days covered by a multi-day report
+#[derive(Debug, Clone)]
+pub struct Period {
+    pub kind: PeriodKind,
+    pub label: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Period {
+    /// Parses an ISO week such as `2026-W42`
+    pub fn parse_week(text: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid week '{}', expected e.g. 2026-W42", text);
+        let (year, week) = text.split_once("-W").ok_or_else(invalid)?;
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let week: u32 = week.parse().map_err(|_| invalid())?;
+        let start = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).ok_or_else(invalid)?;
+        Ok(Self::week_starting(start))
+    }
+
+    /// Parses a month such as `2026-10`
+    pub fn parse_month(text: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid month '{}', expected e.g. 2026-10", text);
+        let (year, month) = text.split_once('-').ok_or_else(invalid)?;
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let month: u32 = month.parse().map_err(|_| invalid())?;
+        let start = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(invalid)?;
+        Ok(Self::month_starting(start))
+    }
+
+    /// An arbitrary span of days, inclusive
+    pub fn range(start: NaiveDate, end: NaiveDate) -> Result<Self, String> {
+        if start > end {
+            return Err(format!("--from ({}) is after --to ({})", start, end));
+        }
+        Ok(Period {
+            kind: PeriodKind::Range,
+            label: format!("{}_{}", start, end),
+            start,
+            end,
+        })
+    }
+
+    fn week_starting(start: NaiveDate) -> Self {
+        let week = start.iso_week();
+        Period {
+            kind: PeriodKind::Week,
+            label: format!("{}-W{:02}", week.year(), week.week()),
+            start,
+            end: start + Duration::days(6),
+        }
+    }
+
+    fn month_starting(start: NaiveDate) -> Self {
+        let next_month = if start.month() == 12 {
+            NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+        }
+        .unwrap();
+        Period {
+            kind: PeriodKind::Month,
+            label: start.format("%Y-%m").to_string(),
+            start,
+            end: next_month - Duration::days(1),
+        }
+    }
+
+    /// The period of the same kind immediately before this one
+    pub fn previous(&self) -> Self {
+        match self.kind {
+            PeriodKind::Week => Self::week_starting(self.start - Duration::days(7)),
+            PeriodKind::Month => {
+                let last_day = self.start - Duration::days(1);
+                Self::month_starting(last_day.with_day(1).unwrap())
+            }
+            PeriodKind::Range => {
+                let length = self.end.signed_duration_since(self.start) + Duration::days(1);
+                Self::range(self.start - length, self.end - length).unwrap()
+            }
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self.kind {
+            PeriodKind::Week => "Weekly",
+            PeriodKind::Month => "Monthly",
+            PeriodKind::Range => "Multi-day",
+        }
+    }
+}
+
+/// One day of a multi-day report
+pub struct DayStats {
+    pub date: NaiveDate,
+    pub blocks: Vec<TimeBlock>,
+    pub summary: DailySummary,
+    /// Time Distribution Score of the day
+    pub score: Option<f64>,
+    /// Start of the first and end of the last non-idle block
+    pub first_activity: Option<DateTime<Local>>,
+    pub last_activity: Option<DateTime<Local>>,
+}
+
+/// How a goal fared over a period
+pub struct GoalSummary {
+    pub name: String,
+    pub description: String,
+    pub days_met: usize,
+    /// Tracked days the goal applied to
+    pub days_applicable: usize,
+    /// Streak as of the last day of the period
+    pub streak: usize,
+}
+
+/// Totals over a whole period
+#[derive(Default)]
+pub struct PeriodTotals {
+    pub productive_time: Duration,
+    pub distracted_time: Duration,
+    pub idle_time: Duration,
+    pub neutral_time: Duration,
+    pub tracked_time: Duration,
+    pub category_breakdown: HashMap<String, Duration>,
+    pub app_switches: usize,
+    pub active_days: usize,
+}
+
+pub struct PeriodStats {
+    pub period: Period,
+    pub days: Vec<DayStats>,
+    pub totals: PeriodTotals,
+    /// Time Distribution Score over the whole period
+    pub score: Option<f64>,
+}
+
+impl PeriodStats {
+    /// Loads every stored day of the period, with corrections applied
+    pub fn load(config: &Config, period: Period) -> Result<Self, String> {
+        let mut days = Vec::new();
+        let mut totals = PeriodTotals::default();
+
+        for date in storage::dates_in_range(period.start, period.end) {
+            let blocks = storage::load_effective_blocks(&config.data_directory, date)?;
+            if blocks.is_empty() {
+                continue;
+            }
+
+            let summary = build_daily_summary(&blocks, date, config.focus_tolerance());
+            totals.productive_time += summary.productive_time;
+            totals.distracted_time += summary.distracted_time;
+            totals.idle_time += summary.idle_time;
+            totals.neutral_time += summary.neutral_time;
+            totals.tracked_time += summary.tracked_time;
+            for (category, duration) in &summary.category_breakdown {
+                *totals.category_breakdown.entry(category.clone()).or_insert(Duration::zero()) += *duration;
+            }
+            totals.app_switches += summary.focus_metrics.app_switches;
+            totals.active_days += 1;
+
+            let active_blocks = || blocks.iter().filter(|block| !block.idle);
+            days.push(DayStats {
+                date,
+                first_activity: active_blocks().map(|block| block.start_time).min(),
+                last_activity: active_blocks().map(|block| block.end_time).max(),
+                score: config.scoring.score_summary(&summary),
+                summary,
+                blocks,
+            });
+        }
+
+        let active_time = totals.tracked_time - totals.idle_time;
+        let switches_per_hour = if active_time > Duration::zero() {
+            totals.app_switches as f64 / (active_time.num_milliseconds() as f64 / 3_600_000.0)
+        } else {
+            0.0
+        };
+        let score = config.scoring.score(&totals.category_breakdown, switches_per_hour);
+
+        Ok(PeriodStats {
+            period,
+            days,
+            totals,
+            score,
+        })
+    }
+
+    /// Average time of day at which work started and ended, measured from the
+    /// configured start of each day so late nights average correctly
+    pub fn average_workday(&self, day_start: chrono::NaiveTime) -> Option<(String, String)> {
+        let mut start_offsets = Vec::new();
+        let mut end_offsets = Vec::new();
+        for day in &self.days {
+            if let (Some(first), Some(last)) = (day.first_activity, day.last_activity) {
+                let day_begins = day_boundary::start_of_day(day.date, day_start);
+                start_offsets.push(first.signed_duration_since(day_begins).num_seconds());
+                end_offsets.push(last.signed_duration_since(day_begins).num_seconds());
+            }
+        }
+        if start_offsets.is_empty() {
+            return None;
+        }
+
+        let average = |offsets: &[i64]| {
+            let mean = offsets.iter().sum::<i64>() / offsets.len() as i64;
+            (day_start + Duration::seconds(mean)).format("%H:%M").to_string()
+        };
+        Some((average(&start_offsets), average(&end_offsets)))
+    }
+
+    pub fn heatmap(&self, category: Option<&str>) -> Heatmap {
+        Heatmap::build(
+            self.period.start,
+            self.period.end,
+            self.days.iter().flat_map(|day| day.blocks.iter()),
+            category,
+        )
+    }
+
+    pub fn goal_summaries(&self, config: &Config) -> Result<Vec<GoalSummary>, String> {
+        let now = Local::now();
+        let days: Vec<(NaiveDate, &[TimeBlock])> = self
+            .days
+            .iter()
+            .map(|day| (day.date, day.blocks.as_slice()))
+            .collect();
+
+        goals::summarize(config, &days, now)
+            .into_iter()
+            .map(|(goal, days_met, days_applicable)| {
+                Ok(GoalSummary {
+                    name: goal.name.clone(),
+                    description: goal.describe(),
+                    days_met,
+                    days_applicable,
+                    streak: goals::streak_before(config, goal, self.period.end + Duration::days(1), now)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Most used applications per ISO week of the period
+    pub fn top_apps_per_week(&self) -> Vec<(String, Vec<(String, Duration)>)> {
+        let mut weeks: Vec<(String, HashMap<String, Duration>)> = Vec::new();
+        for day in &self.days {
+            let iso_week = day.date.iso_week();
+            let label = format!("{}-W{:02}", iso_week.year(), iso_week.week());
+            if weeks.last().map(|(last, _)| last != &label).unwrap_or(true) {
+                weeks.push((label, HashMap::new()));
+            }
+            let apps = &mut weeks.last_mut().unwrap().1;
+            for (app, duration) in &day.summary.application_breakdown {
+                *apps.entry(app.clone()).or_insert(Duration::zero()) += *duration;
+            }
+        }
+
+        weeks
+            .into_iter()
+            .map(|(label, apps)| {
+                let mut apps: Vec<(String, Duration)> = apps.into_iter().collect();
+                apps.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                apps.truncate(TOP_APPS);
+                (label, apps)
+            })
+            .collect()
+    }
+}
+
+/// Generates the HTML and Markdown reports and the heatmap JSON for a period
+/// and returns the paths written. The heatmap shows the productive share
+/// unless `heatmap_category` picks another category.
+pub fn generate(
+    config: &Config,
+    period: Period,
+    heatmap_category: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let previous = PeriodStats::load(config, period.previous())?;
+    let current = PeriodStats::load(config, period)?;
+    let heatmap = current.heatmap(heatmap_category);
+    let goals = current.goal_summaries(config)?;
+
+    let data_directory = Path::new(&config.data_directory);
+    let html_path = data_directory.join(format!("report_{}.html", current.period.label));
+    let markdown_path = data_directory.join(format!("report_{}.md", current.period.label));
+    let heatmap_path = data_directory.join(format!("heatmap_{}.json", current.period.label));
+
+    fs::write(&html_path, render_html(&current, &previous, &heatmap, &goals, config)?)
+        .map_err(|e| format!("Failed to write {}: {}", html_path.display(), e))?;
+    fs::write(&markdown_path, render_markdown(&current, &previous, &heatmap, &goals, config))
+        .map_err(|e| format!("Failed to write {}: {}", markdown_path.display(), e))?;
+    fs::write(&heatmap_path, heatmap.to_json()?)
+        .map_err(|e| format!("Failed to write {}: {}", heatmap_path.display(), e))?;
+
+    Ok(vec![
+        html_path.display().to_string(),
+        markdown_path.display().to_string(),
+        heatmap_path.display().to_string(),
+    ])
+}
+
+/// Relative change between two durations, e.g. "+12.5%"
+fn duration_change(current: Duration, previous: Duration) -> String {
+    if previous <= Duration::zero() {
+        if current > Duration::zero() {
+            "new".to_string()
+        } else {
+            "-".to_string()
+        }
+    } else {
+        let change = (current.num_milliseconds() - previous.num_milliseconds()) as f64
+            / previous.num_milliseconds() as f64
+            * 100.0;
+        format!("{:+.1}%", change)
+    }
+}
+
+/// Change between two scores, in percentage points
+fn score_change(current: Option<f64>, previous: Option<f64>) -> String {
+    match (current, previous) {
+        (Some(current), Some(previous)) => format!("{:+.1} pts", current - previous),
+        _ => "-".to_string(),
+    }
+}
+
+fn format_score(score: Option<f64>) -> String {
+    score
+        .map(|score| format!("{:.1}%", score))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Rows of the "this period vs previous period" comparison:
+/// (label, current, previous, change)
+fn comparison_rows(current: &PeriodStats, previous: &PeriodStats) -> Vec<[String; 4]> {
+    let durations = [
+        ("Focused time", current.totals.productive_time, previous.totals.productive_time),
+        ("Distracted time", current.totals.distracted_time, previous.totals.distracted_time),
+        ("Neutral time", current.totals.neutral_time, previous.totals.neutral_time),
+        ("Idle time", current.totals.idle_time, previous.totals.idle_time),
+        ("Total tracked", current.totals.tracked_time, previous.totals.tracked_time),
+    ];
+
+    let mut rows: Vec<[String; 4]> = durations
+        .iter()
+        .map(|(label, now, before)| {
+            [
+                label.to_string(),
+                format_duration(*now),
+                format_duration(*before),
+                duration_change(*now, *before),
+            ]
+        })
+        .collect();
+    rows.push([
+        "Focus score".to_string(),
+        format_score(current.score),
+        format_score(previous.score),
+        score_change(current.score, previous.score),
+    ]);
+    rows.push([
+        "Active days".to_string(),
+        current.totals.active_days.to_string(),
+        previous.totals.active_days.to_string(),
+        format!(
+            "{:+}",
+            current.totals.active_days as i64 - previous.totals.active_days as i64
+        ),
+    ]);
+    rows
+}
+
+/// Rows of the day-over-day trend: (date, focused, distracted, tracked,
+/// focus score, change vs the previous tracked day)
+fn daily_rows(current: &PeriodStats) -> Vec<[String; 6]> {
+    let mut previous_score = None;
+    current
+        .days
+        .iter()
+        .map(|day| {
+            let score = day.score;
+            let row = [
+                day.date.format("%a %Y-%m-%d").to_string(),
+                format_duration(day.summary.productive_time),
+                format_duration(day.summary.distracted_time),
+                format_duration(day.summary.tracked_time),
+                format_score(score),
+                score_change(score, previous_score),
+            ];
+            if score.is_some() {
+                previous_score = score;
+            }
+            row
+        })
+        .collect()
+}
+
+fn render_markdown(
+    current: &PeriodStats,
+    previous: &PeriodStats,
+    heatmap: &Heatmap,
+    goals: &[GoalSummary],
+    config: &Config,
+) -> String {
+    let period = &current.period;
+    let mut md = format!(
+        "# TimeSense {} Report: {}\n\n{} to {}\n\n",
+        period.title(),
+        period.label,
+        period.start,
+        period.end
+    );
+
+    md.push_str(&format!("## Compared to {}\n\n", previous.period.label));
+    md.push_str("| | This period | Previous period | Change |\n|---|---|---|---|\n");
+    for row in comparison_rows(current, previous) {
+        md.push_str(&markdown_row(&row));
+    }
+
+    md.push_str("\n## Daily Focus\n\n");
+    if current.days.is_empty() {
+        md.push_str("No activity tracked in this period.\n");
+    } else {
+        md.push_str("| Day | Focused | Distracted | Tracked | Focus score | Change |\n|---|---|---|---|---|---|\n");
+        for row in daily_rows(current) {
+            md.push_str(&markdown_row(&row));
+        }
+    }
+
+    if !goals.is_empty() {
+        md.push_str("\n## Goals\n\n| Goal | Target | Met | Streak |\n|---|---|---|---|\n");
+        for goal in goals {
+            md.push_str(&markdown_row(&[
+                goal.name.clone(),
+                goal.description.clone(),
+                format!("{} of {} days", goal.days_met, goal.days_applicable),
+                format!("{} days", goal.streak),
+            ]));
+        }
+    }
+
+    md.push_str("\n## Workday\n\n");
+    match current.average_workday(config.day_start) {
+        Some((start, end)) => md.push_str(&format!(
+            "Average start: {}, average end: {} (over {} active days)\n",
+            start, end, current.totals.active_days
+        )),
+        None => md.push_str("No active days.\n"),
+    }
+
+    md.push_str(&format!(
+        "\n## Weekly Rhythm\n\nShare of tracked time that was {} (%), by weekday and hour:\n\n",
+        heatmap.category
+    ));
+    md.push_str(&heatmap.render_markdown());
+
+    md.push_str("\n## Top Applications\n");
+    for (week, apps) in current.top_apps_per_week() {
+        md.push_str(&format!("\n### {}\n\n| Application | Time |\n|---|---|\n", week));
+        for (app, duration) in apps {
+            md.push_str(&markdown_row(&[app, format_duration(duration)]));
+        }
+    }
+
+    md
+}
+
+/// A Markdown table row; pipes and line breaks in the cells would break the
+/// table, so they are escaped
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| cell.replace('|', "\\|").replace(['\r', '\n'], " "))
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+/// Values `templates/period_report.html` can refer to
+#[derive(Serialize)]
+struct PeriodReportContext<'a> {
+    title: &'static str,
+    label: &'a str,
+    start: NaiveDate,
+    end: NaiveDate,
+    previous_label: &'a str,
+    comparison: Vec<[String; 4]>,
+    days: Vec<DayRow>,
+    goals: Vec<GoalRow<'a>>,
+    workday: Option<Workday>,
+    active_days: usize,
+    heatmap_category: &'a str,
+    /// Generated SVG markup, inserted as-is
+    heatmap_svg: String,
+    top_apps: Vec<WeekApps>,
+}
+
+#[derive(Serialize)]
+struct DayRow {
+    date: String,
+    /// Bar widths relative to the longest tracked day
+    productive_width: f64,
+    distracted_width: f64,
+    focused: String,
+    distracted: String,
+    tracked: String,
+    score: String,
+    change: String,
+}
+
+#[derive(Serialize)]
+struct GoalRow<'a> {
+    name: &'a str,
+    description: &'a str,
+    met_share: f64,
+    days_met: usize,
+    days_applicable: usize,
+    streak: usize,
+}
+
+#[derive(Serialize)]
+struct Workday {
+    start: String,
+    end: String,
+}
+
+#[derive(Serialize)]
+struct WeekApps {
+    label: String,
+    apps: Vec<AppRow>,
+}
+
+#[derive(Serialize)]
+struct AppRow {
+    name: String,
+    time: String,
+}
+
+fn render_html(
+    current: &PeriodStats,
+    previous: &PeriodStats,
+    heatmap: &Heatmap,
+    goals: &[GoalSummary],
+    config: &Config,
+) -> Result<String, String> {
+    let period = &current.period;
+
+    let max_tracked = current
+        .days
+        .iter()
+        .map(|day| day.summary.tracked_time)
+        .max()
+        .unwrap_or_else(Duration::zero);
+    let days = current
+        .days
+        .iter()
+        .zip(daily_rows(current))
+        .map(|(day, [date, focused, distracted, tracked, score, change])| DayRow {
+            date,
+            productive_width: percentage(day.summary.productive_time, max_tracked),
+            distracted_width: percentage(day.summary.distracted_time, max_tracked),
+            focused,
+            distracted,
+            tracked,
+            score,
+            change,
+        })
+        .collect();
+
+    let goals = goals
+        .iter()
+        .map(|goal| GoalRow {
+            name: &goal.name,
+            description: &goal.description,
+            met_share: if goal.days_applicable > 0 {
+                goal.days_met as f64 / goal.days_applicable as f64 * 100.0
+            } else {
+                0.0
+            },
+            days_met: goal.days_met,
+            days_applicable: goal.days_applicable,
+            streak: goal.streak,
+        })
+        .collect();
+
+    let top_apps = current
+        .top_apps_per_week()
+        .into_iter()
+        .map(|(label, apps)| WeekApps {
+            label,
+            apps: apps
+                .into_iter()
+                .map(|(name, duration)| AppRow {
+                    name,
+                    time: format_duration(duration),
+                })
+                .collect(),
+        })
+        .collect();
+
+    let context = PeriodReportContext {
+        title: period.title(),
+        label: &period.label,
+        start: period.start,
+        end: period.end,
+        previous_label: &previous.period.label,
+        comparison: comparison_rows(current, previous),
+        days,
+        goals,
+        workday: current
+            .average_workday(config.day_start)
+            .map(|(start, end)| Workday { start, end }),
+        active_days: current.totals.active_days,
+        heatmap_category: &heatmap.category,
+        heatmap_svg: heatmap.render_svg(),
+        top_apps,
+    };
+    templates::render("period_report.html", &context, &config.reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{NaiveTime, TimeZone};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn span(period: &Period) -> (&str, NaiveDate, NaiveDate) {
+        (period.label.as_str(), period.start, period.end)
+    }
+
+    #[test]
+    fn markdown_cells_cannot_break_the_table() {
+        let row = markdown_row(&["a | b".to_string(), "two\nlines".to_string(), "windows\r\nline".to_string()]);
+        assert_eq!(row, "| a \\| b | two lines | windows  line |\n");
+    }
+
+    #[test]
+    fn iso_weeks_are_parsed() {
+        let week = Period::parse_week("2026-W42").unwrap();
+        assert_eq!(span(&week), ("2026-W42", date(2026, 10, 12), date(2026, 10, 18)));
+
+        // 2026 has 53 ISO weeks, the last one ending in 2027; 2025 has 52
+        let week = Period::parse_week("2026-W53").unwrap();
+        assert_eq!(span(&week), ("2026-W53", date(2026, 12, 28), date(2027, 1, 3)));
+        assert!(Period::parse_week("2025-W53").is_err());
+
+        for invalid in ["2026-W00", "2026-W54", "2026-42", "2026-Wx", "W42"] {
+            assert!(Period::parse_week(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn months_are_parsed() {
+        let month = Period::parse_month("2026-02").unwrap();
+        assert_eq!(span(&month), ("2026-02", date(2026, 2, 1), date(2026, 2, 28)));
+        let month = Period::parse_month("2026-12").unwrap();
+        assert_eq!(month.end, date(2026, 12, 31));
+
+        for invalid in ["2026-13", "2026-00", "2026", "October"] {
+            assert!(Period::parse_month(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn previous_periods_cross_year_boundaries() {
+        let week = Period::parse_week("2027-W01").unwrap().previous();
+        assert_eq!(span(&week), ("2026-W53", date(2026, 12, 28), date(2027, 1, 3)));
+
+        let month = Period::parse_month("2026-03").unwrap().previous();
+        assert_eq!(span(&month), ("2026-02", date(2026, 2, 1), date(2026, 2, 28)));
+        let month = Period::parse_month("2026-01").unwrap().previous();
+        assert_eq!(span(&month), ("2025-12", date(2025, 12, 1), date(2025, 12, 31)));
+
+        let range = Period::range(date(2026, 10, 12), date(2026, 10, 14)).unwrap().previous();
+        assert_eq!(span(&range), ("2026-10-09_2026-10-11", date(2026, 10, 9), date(2026, 10, 11)));
+        assert!(Period::range(date(2026, 10, 14), date(2026, 10, 12)).is_err());
+    }
+
+    #[test]
+    fn the_average_workday_counts_late_nights_toward_their_day() {
+        let at = |day: u32, hour: u32| Local.with_ymd_and_hms(2026, 10, day, hour, 0, 0).unwrap();
+        let day = |date: NaiveDate, activity: Option<(DateTime<Local>, DateTime<Local>)>| DayStats {
+            date,
+            blocks: Vec::new(),
+            summary: build_daily_summary(&[], date, Duration::zero()),
+            score: None,
+            first_activity: activity.map(|(first, _)| first),
+            last_activity: activity.map(|(_, last)| last),
+        };
+        let mut stats = PeriodStats {
+            period: Period::parse_week("2026-W43").unwrap(),
+            days: vec![
+                day(date(2026, 10, 19), Some((at(19, 9), at(20, 1)))),
+                day(date(2026, 10, 20), Some((at(20, 10), at(20, 23)))),
+                // Only idle time
+                day(date(2026, 10, 21), None),
+            ],
+            totals: PeriodTotals::default(),
+            score: None,
+        };
+
+        let four = NaiveTime::from_hms_opt(4, 0, 0).unwrap();
+        assert_eq!(stats.average_workday(four), Some(("09:30".to_string(), "00:00".to_string())));
+
+        stats.days.clear();
+        assert_eq!(stats.average_workday(four), None);
+    }
+}
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// How far below a terminal or editor process to look for the shell (or
+/// other program) whose working directory is the project
+const MAX_PROCESS_DEPTH: usize = 8;
+
+/// Separators editors put between the file, project and application name in
+/// window titles
+const TITLE_SEPARATORS: [&str; 5] = [" - ", " — ", " – ", " | ", " · "];
+
+/// Repositories of each search root, with the root's modification time when
+/// they were listed, so a root is only listed again once an entry is added
+/// or removed
+static REPOSITORIES: Mutex<BTreeMap<PathBuf, (SystemTime, Vec<PathBuf>)>> = Mutex::new(BTreeMap::new());
+
+/// How the git repository behind the focused window is found
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub enabled: bool,
+    /// Editors and terminals whose windows are attributed to a repository
+    /// (the whole application name, case-insensitive and without ".exe")
+    pub apps: Vec<String>,
+    /// Directories holding repositories, e.g. "~/src". A repository directly
+    /// inside one is also recognized when a window title names it.
+    pub search_roots: Vec<String>,
+    /// Record the checked-out branch along with the repository
+    pub record_branch: bool,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> Self {
+        ProjectConfig {
+            enabled: false,
+            apps: [
+                "code", "cursor", "zed", "zed-editor", "vim", "nvim", "gvim", "emacs", "idea", "idea64",
+                "intellij idea", "pycharm", "pycharm64", "rustrover", "rustrover64", "clion", "clion64",
+                "goland", "goland64", "webstorm", "webstorm64", "sublime_text", "sublime text", "terminal",
+                "gnome-terminal-", "gnome-terminal-server", "xfce4-terminal", "windowsterminal", "konsole",
+                "alacritty", "kitty", "wezterm", "wezterm-gui", "foot", "xterm", "tilix", "terminator",
+                "iterm2",
+            ]
+            .iter()
+            .map(|app| app.to_string())
+            .collect(),
+            search_roots: Vec::new(),
+            record_branch: true,
+        }
+    }
+}
+
+/// A git repository and the branch checked out in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repository {
+    /// Name of the repository's directory
+    pub name: String,
+    /// Branch name, or the short commit hash when the HEAD is detached
+    pub branch: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Whether `application` is one of `apps`. Names are compared whole, so
+    /// "code" doesn't match "Xcode".
+    fn is_project_app(&self, application: &str) -> bool {
+        let application = application.trim().to_lowercase();
+        let name = application.strip_suffix(".exe").unwrap_or(&application);
+        self.apps.iter().any(|app| app.trim().to_lowercase() == name)
+    }
+
+    /// The repository the focused window belongs to, found from (in order) a
+    /// path in its title, a repository of the search roots named in its
+    /// title, or the working directory of its process or the programs running
+    /// inside it (Linux only)
+    pub fn resolve(&self, application: &str, title: Option<&str>, pid: Option<u32>) -> Option<Repository> {
+        if !self.enabled || !self.is_project_app(application) {
+            return None;
+        }
+        let root = title
+            .and_then(repository_of_title_path)
+            .or_else(|| title.and_then(|title| self.repository_named_in(title)))
+            .or_else(|| pid.and_then(repository_of_process))?;
+
+        Some(Repository {
+            name: root.file_name()?.to_string_lossy().to_string(),
+            branch: if self.record_branch { current_branch(&root) } else { None },
+        })
+    }
+
+    /// The search-root repository whose directory name is one of the title's
+    /// parts, e.g. "timesense" in "main.rs - timesense - Visual Studio Code"
+    fn repository_named_in(&self, title: &str) -> Option<PathBuf> {
+        let mut parts = vec![title.to_string()];
+        for separator in TITLE_SEPARATORS {
+            parts = parts.iter().flat_map(|part| part.split(separator)).map(str::to_string).collect();
+        }
+        let parts: Vec<String> = parts
+            .iter()
+            .map(|part| part.trim_matches(|c: char| c.is_whitespace() || "●*[]()".contains(c)).to_lowercase())
+            .collect();
+
+        self.repositories().into_iter().find(|repository| {
+            repository
+                .file_name()
+                .is_some_and(|name| parts.contains(&name.to_string_lossy().to_lowercase()))
+        })
+    }
+
+    /// The repositories in the search roots
+    pub fn repositories(&self) -> Vec<PathBuf> {
+        let mut cached = REPOSITORIES.lock().unwrap();
+        let mut repositories = Vec::new();
+        for root in self.search_roots.iter().map(|root| expand_home(root)) {
+            let Ok(modified) = fs::metadata(&root).and_then(|metadata| metadata.modified()) else {
+                cached.remove(&root);
+                continue;
+            };
+            match cached.get(&root) {
+                Some((listed, found)) if *listed == modified => repositories.extend(found.iter().cloned()),
+                _ => {
+                    let found = repositories_in(&root);
+                    repositories.extend(found.iter().cloned());
+                    cached.insert(root, (modified, found));
+                }
+            }
+        }
+        repositories
+    }
+}
+
+/// "~/src" -> "$HOME/src"
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ if path == "~" => env::var_os("HOME").map(PathBuf::from).unwrap_or_default(),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// The closest directory at or above `path` containing `.git` (a directory,
+/// or a file for worktrees and submodules)
+fn repository_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// `root` itself if it is a repository, and the repositories directly inside it
+fn repositories_in(root: &Path) -> Vec<PathBuf> {
+    let mut repositories: Vec<PathBuf> = Vec::new();
+    if root.join(".git").exists() {
+        repositories.push(root.to_path_buf());
+    }
+    if let Ok(entries) = fs::read_dir(root) {
+        repositories.extend(
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.join(".git").exists()),
+        );
+    }
+    repositories
+}
+
+/// The repository of an absolute or home-relative path in a window title,
+/// such as "~/src/timesense" in a terminal's "me@host: ~/src/timesense"
+fn repository_of_title_path(title: &str) -> Option<PathBuf> {
+    title
+        .split(|c: char| c.is_whitespace() || "[]()\"'".contains(c))
+        .map(|word| word.trim_end_matches([':', ',', ';']))
+        .filter(|word| word.starts_with('/') || word.starts_with('~'))
+        .find_map(|word| repository_root(&expand_home(word)))
+}
+
+/// The repository of the working directory of `pid` or of the newest process
+/// running below it, preferring the deepest one: a terminal emulator's own
+/// directory rarely matters, the shell's in it does
+fn repository_of_process(pid: u32) -> Option<PathBuf> {
+    let mut chain = vec![pid];
+    while chain.len() < MAX_PROCESS_DEPTH {
+        let last = chain[chain.len() - 1];
+        let children = fs::read_to_string(format!("/proc/{}/task/{}/children", last, last)).unwrap_or_default();
+        match children.split_whitespace().filter_map(|child| child.parse::<u32>().ok()).max() {
+            Some(child) => chain.push(child),
+            None => break,
+        }
+    }
+
+    chain.iter().rev().find_map(|pid| {
+        let cwd = fs::read_link(format!("/proc/{}/cwd", pid)).ok()?;
+        repository_root(&cwd)
+    })
+}
+
+/// The branch checked out in the repository at `root`, read from its `HEAD`
+fn current_branch(root: &Path) -> Option<String> {
+    let dot_git = root.join(".git");
+    let git_dir = if dot_git.is_file() {
+        // Worktrees and submodules point to their git directory
+        let contents = fs::read_to_string(&dot_git).ok()?;
+        root.join(contents.trim().strip_prefix("gitdir:")?.trim())
+    } else {
+        dot_git
+    };
+
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(reference.strip_prefix("refs/heads/").unwrap_or(reference).to_string()),
+        None => head.get(..7).map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apps_match_whole_names() {
+        let config = ProjectConfig::default();
+        assert!(config.is_project_app("code"));
+        assert!(config.is_project_app("Code"));
+        assert!(config.is_project_app("Code.exe"));
+        assert!(config.is_project_app("iTerm2"));
+        assert!(!config.is_project_app("Xcode"));
+        assert!(!config.is_project_app("codeblocks"));
+        assert!(!config.is_project_app("exe"));
+    }
+
+    #[test]
+    fn repositories_are_listed_again_when_a_root_changes() {
+        let root = std::env::temp_dir().join(format!("timesense-projects-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("alpha/.git")).unwrap();
+        fs::create_dir_all(root.join("notes")).unwrap();
+        let config = ProjectConfig {
+            search_roots: vec![root.to_string_lossy().into_owned()],
+            ..ProjectConfig::default()
+        };
+        assert_eq!(config.repositories(), vec![root.join("alpha")]);
+
+        fs::create_dir_all(root.join("beta/.git")).unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(60);
+        fs::File::open(&root).unwrap().set_modified(later).unwrap();
+        let mut repositories = config.repositories();
+        repositories.sort();
+        assert_eq!(repositories, vec![root.join("alpha"), root.join("beta")]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
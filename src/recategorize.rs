@@ -0,0 +1,212 @@
+use chrono::{Duration, NaiveDate};
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::storage;
+use crate::time_format::format_duration;
+use crate::TimeBlock;
+
+/// Time moved from one category (first) to another (second)
+type Moves = BTreeMap<(String, String), Duration>;
+
+/// Re-applies the current rules and calendars to the stored blocks of every
+/// day in the range. With `dry_run` nothing is written; only the minutes that
+/// would move between categories are reported.
+pub fn run(config: &Config, from: NaiveDate, to: NaiveDate, dry_run: bool) -> Result<(), String> {
+    if from > to {
+        return Err(format!("--from ({}) is after --to ({})", from, to));
+    }
+
+    let (days_changed, moves) = recategorize(config, from, to, dry_run)?;
+    if days_changed == 0 {
+        println!("All stored blocks already match the current rules and calendars.");
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "{} {} day(s):",
+        if dry_run { "Would change" } else { "Changed" },
+        days_changed
+    );
+    for ((old_type, new_type), duration) in &moves {
+        println!(
+            "  {:>8}  {} -> {}",
+            format_duration(*duration),
+            old_type,
+            new_type
+        );
+    }
+    if dry_run {
+        println!("Dry run: nothing was written.");
+    }
+
+    Ok(())
+}
+
+/// Recategorizes each stored day of the range, returning the number of days
+/// that changed and the time that moved between categories
+fn recategorize(config: &Config, from: NaiveDate, to: NaiveDate, dry_run: bool) -> Result<(usize, Moves), String> {
+    let mut moves = Moves::new();
+    let mut days_changed = 0;
+
+    for date in storage::dates_in_range(from, to) {
+        let old_blocks = storage::load_blocks(&config.data_directory, date)?;
+        if old_blocks.is_empty() {
+            continue;
+        }
+
+        let mut blocks = old_blocks.clone();
+        for block in &mut blocks {
+            block.activity_type = crate::categorize_activity(&block.application, block.project.as_deref(), config);
+            block.meeting = None;
+        }
+        let blocks = config.calendar.label(&blocks);
+
+        add_moves(&old_blocks, &blocks, &mut moves);
+        let changed_blocks = changed_blocks(&old_blocks, &blocks);
+        if changed_blocks == 0 {
+            println!("{}: no changes", date);
+            continue;
+        }
+
+        days_changed += 1;
+        println!("{}: {} block(s) re-categorized", date, changed_blocks);
+
+        if !dry_run {
+            storage::save_blocks(&config.data_directory, date, &blocks)?;
+            crate::regenerate_day(config, date)?;
+        }
+    }
+
+    Ok((days_changed, moves))
+}
+
+/// Adds up the time that changed category. Blocks may have been split at
+/// meeting boundaries, so the categories are compared over time rather than
+/// block by block, walking both time-ordered lists together.
+fn add_moves(old_blocks: &[TimeBlock], blocks: &[TimeBlock], moves: &mut Moves) {
+    let (mut old_index, mut index) = (0, 0);
+    while let (Some(old), Some(block)) = (old_blocks.get(old_index), blocks.get(index)) {
+        let overlap = block
+            .end_time
+            .min(old.end_time)
+            .signed_duration_since(block.start_time.max(old.start_time));
+        if overlap > Duration::zero() && old.activity_type != block.activity_type {
+            *moves
+                .entry((old.activity_type.clone(), block.activity_type.clone()))
+                .or_insert(Duration::zero()) += overlap;
+        }
+        // Move past whichever of the two ends first
+        if old.end_time <= block.end_time {
+            old_index += 1;
+        } else {
+            index += 1;
+        }
+    }
+}
+
+/// Number of blocks that differ from the stored block starting at the same time
+fn changed_blocks(old_blocks: &[TimeBlock], blocks: &[TimeBlock]) -> usize {
+    let mut old_blocks = old_blocks.iter().peekable();
+    blocks
+        .iter()
+        .filter(|block| {
+            while old_blocks.next_if(|old| old.start_time < block.start_time).is_some() {}
+            old_blocks.peek() != Some(block)
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::CalendarConfig;
+    use crate::config::CategoryRule;
+    use chrono::{DateTime, Local, TimeZone};
+    use std::fs;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 19, hour, minute, 0).unwrap()
+    }
+
+    fn block(start: DateTime<Local>, end: DateTime<Local>, app: &str, activity_type: &str) -> TimeBlock {
+        let mut block = TimeBlock::new(start, app.to_string(), activity_type.to_string(), false);
+        block.end_time = end;
+        block
+    }
+
+    fn rule(app: &str, category: &str) -> CategoryRule {
+        CategoryRule {
+            app: app.to_string(),
+            category: category.to_string(),
+            project: None,
+        }
+    }
+
+    #[test]
+    fn stored_days_follow_new_rules_and_meetings() {
+        let dir = std::env::temp_dir().join(format!("timesense-recategorize-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let calendar = dir.join("work.ics");
+        fs::write(
+            &calendar,
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Standup\r\nDTSTART:20261019T100000\r\nDTEND:20261019T110000\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        let config = Config {
+            data_directory: dir.to_string_lossy().into_owned(),
+            rules: vec![rule("editor", "productive"), rule("chat", "distraction")],
+            calendar: CalendarConfig {
+                files: vec![calendar.to_string_lossy().into_owned()],
+                ..CalendarConfig::default()
+            },
+            ..Config::default()
+        };
+        let date = at(0, 0).date_naive();
+        let stored = vec![
+            block(at(9, 0), at(9, 30), "editor", "productive"),
+            block(at(9, 30), at(10, 30), "editor", "neutral"),
+            block(at(10, 30), at(11, 30), "chat", "productive"),
+        ];
+        storage::save_blocks(&config.data_directory, date, &stored).unwrap();
+
+        // A dry run reports the moves but leaves the day alone
+        let (days_changed, dry_moves) = recategorize(&config, date, date, true).unwrap();
+        assert_eq!(days_changed, 1);
+        assert_eq!(storage::load_blocks(&config.data_directory, date).unwrap(), stored);
+
+        let (days_changed, moves) = recategorize(&config, date, date, false).unwrap();
+        assert_eq!(days_changed, 1);
+        assert_eq!(moves, dry_moves);
+        let moved = |from: &str, to: &str| moves.get(&(from.to_string(), to.to_string())).copied();
+        assert_eq!(moved("neutral", "productive"), Some(Duration::minutes(30)));
+        assert_eq!(moved("neutral", "meeting"), Some(Duration::minutes(30)));
+        assert_eq!(moved("productive", "meeting"), Some(Duration::minutes(30)));
+        assert_eq!(moved("productive", "distraction"), Some(Duration::minutes(30)));
+        assert_eq!(moves.len(), 4);
+
+        let blocks = storage::load_blocks(&config.data_directory, date).unwrap();
+        let spans: Vec<_> = blocks
+            .iter()
+            .map(|block| (block.start_time, block.end_time, block.activity_type.as_str(), block.meeting.as_deref()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (at(9, 0), at(9, 30), "productive", None),
+                (at(9, 30), at(10, 0), "productive", None),
+                (at(10, 0), at(10, 30), "meeting", Some("Standup")),
+                (at(10, 30), at(11, 0), "meeting", Some("Standup")),
+                (at(11, 0), at(11, 30), "distraction", None),
+            ]
+        );
+        assert_eq!(changed_blocks(&stored, &blocks), 4);
+
+        // Running again changes nothing
+        let (days_changed, moves) = recategorize(&config, date, date, false).unwrap();
+        assert_eq!((days_changed, moves.len()), (0, 0));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,258 +1,200 @@
 use std::fs;
 use chrono::Duration as TimeDelta;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use crate::calendar::MeetingSummary;
 use crate::fuzzy_match::group_similar_apps;
+use crate::goals::GoalProgress;
+use crate::metrics::FocusMetrics;
+use crate::scoring::ScoringModel;
+use crate::templates::{self, ReportSettings};
+use crate::time_format::{format_duration, percentage};
+use crate::timeline::{category_color, render_timeline_svg};
 
 pub struct ReportGenerator {
     data_directory: String,
+    scoring: ScoringModel,
+    settings: ReportSettings,
 }
 
-impl ReportGenerator {
-    pub fn new(data_directory: String) -> Self {
-        ReportGenerator { data_directory }
-    }
+/// Everything `templates/daily_report.html` can refer to (documented in the
+/// README). Values are plain text; the template escapes them.
+#[derive(Serialize)]
+struct DailyReportContext<'a> {
+    /// The raw summary and blocks, for custom templates
+    summary: &'a super::DailySummary,
+    blocks: &'a [super::TimeBlock],
+    metrics: &'a FocusMetrics,
+    /// Active time per category, plus idle time, longest first
+    categories: Vec<CategoryRow>,
+    date: String,
+    score: String,
+    rating: String,
+    rating_class: &'static str,
+    totals: Totals,
+    shares: Shares,
+    focus_cards: Vec<StatCard>,
+    goals: Vec<GoalRow>,
+    /// Scheduled meetings against time in call apps; `None` without calendars
+    meetings: Option<Meetings>,
+    /// Time per project with its branches, longest first
+    projects: Vec<ProjectRow>,
+    /// Generated SVG markup, inserted as-is
+    timeline_svg: String,
+    applications: Vec<TableRow>,
+    activities: Vec<TableRow>,
+    observations: String,
+}
 
-    pub fn generate_report(&self, summary: &super::DailySummary) {
-        // Create a user-friendly HTML report
-        let productive_minutes = summary.productive_time.num_minutes() as f64;
-        let distracted_minutes = summary.distracted_time.num_minutes() as f64;
-        let idle_minutes = summary.idle_time.num_minutes() as f64;
+#[derive(Serialize)]
+struct Totals {
+    productive: String,
+    distracted: String,
+    neutral: String,
+    idle: String,
+    tracked: String,
+}
 
-        let total_minutes = productive_minutes + distracted_minutes + idle_minutes;
-        
-        // Calculate percentages with proper handling of zero total time
-        let (productive_percentage, distracted_percentage, idle_percentage) = if total_minutes > 0.0 {
-            (
-                (productive_minutes / total_minutes) * 100.0,
-                (distracted_minutes / total_minutes) * 100.0,
-                (idle_minutes / total_minutes) * 100.0
-            )
-        } else {
-            (0.0, 0.0, 0.0)
-        };
+/// Percentages of the tracked time
+#[derive(Serialize)]
+struct Shares {
+    productive: f64,
+    distracted: f64,
+    neutral: f64,
+    idle: f64,
+}
 
-        // Calculate time distribution score (0-100) based on productive vs distracted time
-        let time_distribution_score = if total_minutes > 0.0 {
-            let active_time = productive_minutes + distracted_minutes;
-            if active_time > 0.0 {
-                (productive_minutes / active_time) * 100.0
-            } else {
-                100.0 // If no active time, assume focused
-            }
+#[derive(Serialize)]
+struct StatCard {
+    value: String,
+    detail: Option<String>,
+    label: &'static str,
+}
+
+#[derive(Serialize)]
+struct GoalRow {
+    name: String,
+    description: String,
+    state: &'static str,
+    state_class: &'static str,
+    /// Bar width, capped at 100
+    percent: f64,
+    amount: String,
+    streak: String,
+}
+
+#[derive(Serialize)]
+struct Meetings {
+    scheduled: String,
+    call_time: String,
+    unscheduled_call_time: String,
+    rows: Vec<MeetingRow>,
+}
+
+#[derive(Serialize)]
+struct MeetingRow {
+    title: String,
+    /// "09:00-09:30"
+    time: String,
+    scheduled: String,
+    call_time: String,
+}
+
+#[derive(Serialize)]
+struct ProjectRow {
+    name: String,
+    time: String,
+    share: f64,
+    /// Time per branch; `share` is the percentage of the project's time
+    branches: Vec<TableRow>,
+}
+
+#[derive(Serialize)]
+struct CategoryRow {
+    name: String,
+    color: &'static str,
+    time: String,
+    share: f64,
+}
+
+#[derive(Serialize)]
+pub struct TableRow {
+    name: String,
+    time: String,
+    share: f64,
+}
+
+impl ReportGenerator {
+    pub fn new(data_directory: String, scoring: ScoringModel, settings: ReportSettings) -> Self {
+        ReportGenerator {
+            data_directory,
+            scoring,
+            settings,
+        }
+    }
+
+    pub fn generate_report(&self, summary: &super::DailySummary, blocks: &[super::TimeBlock], goals: &[GoalProgress], meetings: &MeetingSummary) {
+        // Create a user-friendly HTML report. All figures are computed from
+        // exact durations and only rounded for display.
+        let total_time = if summary.tracked_time > TimeDelta::zero() {
+            summary.tracked_time
         } else {
-            100.0 // If no time tracked, assume focused
+            // Summaries written before tracked_time existed
+            summary.productive_time + summary.distracted_time + summary.idle_time + summary.neutral_time
         };
 
-        // Get time distribution rating
-        let time_distribution_rating = self.get_time_distribution_rating(time_distribution_score);
+        // Time distribution score (0-100) from the configured scoring model
+        let time_distribution_score = self.scoring.score_summary(summary);
+        let (time_distribution_rating, time_distribution_class) = self.scoring.rate(time_distribution_score);
 
-        let html = format!(
-            r#"<!DOCTYPE html>
-<html>
-<head>
-    <title>TimeSense Daily Report - {}</title>
-    <style>
-        body {{ 
-            font-family: 'Segoe UI', Arial, sans-serif; 
-            margin: 0;
-            padding: 0;
-            background-color: #f5f5f5;
-            color: #333;
-        }}
-        .container {{
-            max-width: 1000px;
-            margin: 0 auto;
-            padding: 20px;
-        }}
-        header {{
-            background-color: #2c3e50;
-            color: white;
-            padding: 20px;
-            text-align: center;
-            border-radius: 5px 5px 0 0;
-        }}
-        .content {{
-            background-color: white;
-            padding: 20px;
-            border-radius: 0 0 5px 5px;
-            box-shadow: 0 2px 5px rgba(0,0,0,0.1);
-        }}
-        .summary {{ 
-            margin-bottom: 30px;
-            padding: 20px;
-            background-color: #f9f9f9;
-            border-radius: 5px;
-        }}
-        .chart {{ 
-            width: 100%; 
-            background-color: #f0f0f0; 
-            height: 30px; 
-            margin-bottom: 15px;
-            border-radius: 15px;
-            overflow: hidden;
-        }}
-        .productive {{ background-color: #4CAF50; height: 100%; float: left; }}
-        .distracted {{ background-color: #F44336; height: 100%; float: left; }}
-        .idle {{ background-color: #9E9E9E; height: 100%; float: left; }}
-        table {{ 
-            border-collapse: collapse; 
-            width: 100%;
-            margin-bottom: 20px;
-        }}
-        th, td {{ 
-            text-align: left; 
-            padding: 12px 8px; 
-            border-bottom: 1px solid #ddd; 
-        }}
-        th {{
-            background-color: #f2f2f2;
-            font-weight: bold;
-        }}
-        tr:hover {{
-            background-color: #f5f5f5;
-        }}
-        .time-distribution-score {{
-            font-size: 24px;
-            font-weight: bold;
-            text-align: center;
-            margin: 20px 0;
-            padding: 10px;
-            border-radius: 5px;
-        }}
-        .high-distribution {{
-            background-color: #dff0d8;
-            color: #3c763d;
-        }}
-        .medium-distribution {{
-            background-color: #fcf8e3;
-            color: #8a6d3b;
-        }}
-        .low-distribution {{
-            background-color: #f2dede;
-            color: #a94442;
-        }}
-        .section {{
-            margin-bottom: 30px;
-        }}
-        .section-title {{
-            border-bottom: 2px solid #2c3e50;
-            padding-bottom: 10px;
-            margin-bottom: 15px;
-        }}
-        .stats-grid {{
-            display: grid;
-            grid-template-columns: repeat(3, 1fr);
-            gap: 20px;
-            margin-bottom: 20px;
-        }}
-        .stat-card {{
-            background-color: #f9f9f9;
-            padding: 15px;
-            border-radius: 5px;
-            text-align: center;
-        }}
-        .stat-value {{
-            font-size: 24px;
-            font-weight: bold;
-            margin-bottom: 5px;
-        }}
-        .stat-label {{
-            color: #666;
-        }}
-        .productive-value {{ color: #4CAF50; }}
-        .distracted-value {{ color: #F44336; }}
-        .idle-value {{ color: #9E9E9E; }}
-    </style>
-</head>
-<body>
-    <div class="container">
-        <header>
-            <h1>TimeSense Daily Report</h1>
-            <h2>{}</h2>
-        </header>
-        
-        <div class="content">
-            <div class="time-distribution-score {}">
-                Time Distribution Score: {:.1}% - {}
-            </div>
-            
-            <div class="section">
-                <h3 class="section-title">Time Distribution</h3>
-                <div class="stats-grid">
-                    <div class="stat-card">
-                        <div class="stat-value productive-value">{:.0} minutes</div>
-                        <div class="stat-label">Focused Time</div>
-                    </div>
-                    <div class="stat-card">
-                        <div class="stat-value distracted-value">{:.0} minutes</div>
-                        <div class="stat-label">Distracted Time</div>
-                    </div>
-                    <div class="stat-card">
-                        <div class="stat-value idle-value">{:.0} minutes</div>
-                        <div class="stat-label">Idle Time</div>
-                    </div>
-                </div>
-                
-                <div class="chart">
-                    <div class="productive" style="width: {}%"></div>
-                    <div class="distracted" style="width: {}%"></div>
-                    <div class="idle" style="width: {}%"></div>
-                </div>
-            </div>
-            
-            <div class="section">
-                <h3 class="section-title">Application Usage</h3>
-                <table class="app-table">
-                    <tr>
-                        <th>Application</th>
-                        <th>Minutes</th>
-                        <th>Percentage</th>
-                    </tr>
-                    {}
-                </table>
-            </div>
-            
-            <div class="section">
-                <h3 class="section-title">Activity Distribution</h3>
-                <table>
-                    <tr>
-                        <th>Activity Type</th>
-                        <th>Minutes</th>
-                        <th>Percentage</th>
-                    </tr>
-                    {}
-                </table>
-            </div>
-            
-            <div class="section">
-                <h3 class="section-title">Time Distribution Observations</h3>
-                <p>{}</p>
-            </div>
-        </div>
-    </div>
-</body>
-</html>"#,
-            summary.date,
-            summary.date,
-            self.get_time_distribution_class(time_distribution_score),
-            time_distribution_score,
-            time_distribution_rating,
-            productive_minutes,
-            distracted_minutes,
-            idle_minutes,
-            productive_percentage,
-            distracted_percentage,
-            idle_percentage,
-            self.generate_application_table(&summary.application_breakdown, total_minutes),
-            self.generate_activity_table(&summary.activity_breakdown, total_minutes),
-            self.generate_time_distribution_observations(summary)
-        );
+        let context = DailyReportContext {
+            summary,
+            blocks,
+            metrics: &summary.focus_metrics,
+            categories: self.generate_category_rows(summary, total_time),
+            date: summary.date.clone(),
+            score: time_distribution_score
+                .map(|score| format!("{:.1}%", score))
+                .unwrap_or_else(|| "-".to_string()),
+            rating: time_distribution_rating.to_string(),
+            rating_class: time_distribution_class,
+            totals: Totals {
+                productive: format_duration(summary.productive_time),
+                distracted: format_duration(summary.distracted_time),
+                neutral: format_duration(summary.neutral_time),
+                idle: format_duration(summary.idle_time),
+                tracked: format_duration(total_time),
+            },
+            shares: Shares {
+                productive: percentage(summary.productive_time, total_time),
+                distracted: percentage(summary.distracted_time, total_time),
+                neutral: percentage(summary.neutral_time, total_time),
+                idle: percentage(summary.idle_time, total_time),
+            },
+            focus_cards: self.generate_focus_metric_cards(&summary.focus_metrics),
+            goals: self.generate_goal_rows(goals),
+            meetings: self.generate_meetings(meetings),
+            projects: self.generate_project_rows(blocks, total_time),
+            timeline_svg: render_timeline_svg(blocks),
+            applications: self.generate_application_table(&summary.application_breakdown, total_time),
+            activities: self.generate_activity_table(&summary.activity_breakdown, total_time),
+            observations: self.generate_time_distribution_observations(summary),
+        };
 
+        let html = match templates::render("daily_report.html", &context, &self.settings) {
+            Ok(html) => html,
+            Err(e) => {
+                // Most likely a broken custom template
+                println!("{}", e);
+                return;
+            }
+        };
         let filename = format!("{}/report_{}.html", self.data_directory, summary.date);
         fs::write(filename, html).expect("Failed to write HTML report");
     }
 
-    pub fn generate_application_table(&self, app_breakdown: &HashMap<String, TimeDelta>, total_minutes: f64) -> String {
+    pub fn generate_application_table(&self, app_breakdown: &HashMap<String, TimeDelta>, total_time: TimeDelta) -> Vec<TableRow> {
         // Group similar app names together
         let grouped_apps = group_similar_apps(app_breakdown);
         
@@ -260,138 +202,214 @@ impl ReportGenerator {
         let mut app_vec: Vec<(&String, &(TimeDelta, Vec<(String, TimeDelta)>))> = grouped_apps.iter().collect();
         app_vec.sort_by(|a, b| b.1.0.cmp(&a.1.0));
 
-        let mut rows = String::new();
-        for (app_name, (duration, _)) in app_vec {
-            let minutes = duration.num_minutes() as f64;
-            let percentage = if total_minutes > 0.0 {
-                (minutes / total_minutes) * 100.0
-            } else {
-                0.0
-            };
-
-            rows.push_str(&format!(
-                "<tr><td>{}</td><td>{:.0}</td><td>{:.1}%</td></tr>",
-                app_name,
-                minutes,
-                percentage
-            ));
-        }
-
-        rows
+        app_vec
+            .into_iter()
+            .map(|(app_name, (duration, _))| TableRow {
+                name: app_name.clone(),
+                time: format_duration(*duration),
+                share: percentage(*duration, total_time),
+            })
+            .collect()
     }
     
-    fn generate_activity_table(&self, activity_breakdown: &HashMap<String, TimeDelta>, total_minutes: f64) -> String {
+    fn generate_activity_table(&self, activity_breakdown: &HashMap<String, TimeDelta>, total_time: TimeDelta) -> Vec<TableRow> {
         // Sort activities by duration (descending)
         let mut sorted_activities: Vec<(&String, &TimeDelta)> = activity_breakdown.iter().collect();
         sorted_activities.sort_by(|a, b| b.1.cmp(a.1));
         
         sorted_activities
             .iter()
-            .map(|(activity, duration)| {
-                let minutes = duration.num_minutes() as f64;
-                let percentage = if total_minutes > 0.0 {
-                    (minutes / total_minutes) * 100.0
-                } else {
-                    0.0
-                };
-                format!(
-                    "<tr><td>{}</td><td>{:.0}</td><td>{:.1}%</td></tr>",
-                    activity,
-                    minutes,
-                    percentage
-                )
+            .map(|(activity, duration)| TableRow {
+                name: activity.to_string(),
+                time: format_duration(**duration),
+                share: percentage(**duration, total_time),
             })
-            .collect::<Vec<String>>()
-            .join("")
+            .collect()
     }
     
-    fn get_time_distribution_rating(&self, score: f64) -> String {
-        if score >= 80.0 {
-            "High Focus".to_string()
-        } else if score >= 60.0 {
-            "Moderate Focus".to_string()
-        } else if score >= 40.0 {
-            "Balanced".to_string()
-        } else if score > 0.0 {
-            "Distracted".to_string()
-        } else {
-            "High Focus".to_string() // Default to High Focus when no activity
+    fn generate_category_rows(&self, summary: &super::DailySummary, total_time: TimeDelta) -> Vec<CategoryRow> {
+        let mut categories: Vec<(&str, TimeDelta, bool)> = summary
+            .category_breakdown
+            .iter()
+            .map(|(category, duration)| (category.as_str(), *duration, false))
+            .collect();
+        if summary.idle_time > TimeDelta::zero() {
+            categories.push(("idle", summary.idle_time, true));
         }
+        categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        categories
+            .into_iter()
+            .map(|(category, duration, idle)| CategoryRow {
+                name: category.to_string(),
+                color: category_color(category, idle),
+                time: format_duration(duration),
+                share: percentage(duration, total_time),
+            })
+            .collect()
     }
     
-    fn get_time_distribution_class(&self, score: f64) -> String {
-        if score >= 60.0 {
-            "high-distribution".to_string()
-        } else if score >= 40.0 {
-            "medium-distribution".to_string()
-        } else {
-            "low-distribution".to_string()
-        }
+    fn generate_focus_metric_cards(&self, metrics: &FocusMetrics) -> Vec<StatCard> {
+        let or_none = |duration: Option<TimeDelta>| duration.map(format_duration).unwrap_or_else(|| "-".to_string());
+        let (deep_work, deep_work_span) = match &metrics.longest_deep_work {
+            Some(session) => (
+                format_duration(session.focused_time),
+                Some(format!("{} - {}", session.start.format("%H:%M"), session.end.format("%H:%M"))),
+            ),
+            None => ("-".to_string(), None),
+        };
+
+        vec![
+            StatCard {
+                value: format!("{:.1}", metrics.switches_per_hour),
+                detail: None,
+                label: "App Switches per Hour",
+            },
+            StatCard {
+                value: or_none(metrics.median_focus_stretch),
+                detail: None,
+                label: "Median Focus Stretch",
+            },
+            StatCard {
+                value: deep_work,
+                detail: deep_work_span,
+                label: "Longest Deep Work",
+            },
+            StatCard {
+                value: or_none(metrics.median_return_to_focus),
+                detail: None,
+                label: "Time to Return to Focus",
+            },
+        ]
+    }
+    
+    fn generate_goal_rows(&self, goals: &[GoalProgress]) -> Vec<GoalRow> {
+        goals
+            .iter()
+            .map(|progress| GoalRow {
+                name: progress.goal.name.clone(),
+                description: progress.goal.describe(),
+                state: progress.state.label(),
+                state_class: progress.state.css_class(),
+                percent: progress.percent().min(100.0),
+                amount: progress.amount(),
+                streak: progress.streak_text(),
+            })
+            .collect()
     }
     
+    fn generate_meetings(&self, summary: &MeetingSummary) -> Option<Meetings> {
+        if summary.meetings.is_empty() && summary.call_time <= TimeDelta::zero() {
+            return None;
+        }
+        Some(Meetings {
+            scheduled: format_duration(summary.scheduled),
+            call_time: format_duration(summary.call_time),
+            unscheduled_call_time: format_duration(summary.unscheduled_call_time),
+            rows: summary
+                .meetings
+                .iter()
+                .map(|meeting| MeetingRow {
+                    title: meeting.title.clone(),
+                    time: format!("{}-{}", meeting.start.format("%H:%M"), meeting.end.format("%H:%M")),
+                    scheduled: format_duration(meeting.end.signed_duration_since(meeting.start)),
+                    call_time: format_duration(meeting.call_time),
+                })
+                .collect(),
+        })
+    }
+
+    fn generate_project_rows(&self, blocks: &[super::TimeBlock], total_time: TimeDelta) -> Vec<ProjectRow> {
+        let mut project_times: BTreeMap<&str, TimeDelta> = BTreeMap::new();
+        let mut branch_times: BTreeMap<(&str, &str), TimeDelta> = BTreeMap::new();
+        for block in blocks {
+            let Some(project) = &block.project else {
+                continue;
+            };
+            let duration = block.end_time.signed_duration_since(block.start_time);
+            *project_times.entry(project).or_insert(TimeDelta::zero()) += duration;
+            if let Some(branch) = &block.branch {
+                *branch_times.entry((project, branch)).or_insert(TimeDelta::zero()) += duration;
+            }
+        }
+
+        let mut projects: Vec<(&str, TimeDelta)> = project_times.into_iter().collect();
+        projects.sort_by_key(|(_, duration)| Reverse(*duration));
+        projects
+            .into_iter()
+            .map(|(project, duration)| {
+                let mut branches: Vec<(&str, TimeDelta)> = branch_times
+                    .iter()
+                    .filter(|((branch_project, _), _)| *branch_project == project)
+                    .map(|((_, branch), branch_time)| (*branch, *branch_time))
+                    .collect();
+                branches.sort_by_key(|(_, branch_time)| Reverse(*branch_time));
+                ProjectRow {
+                    name: project.to_string(),
+                    time: format_duration(duration),
+                    share: percentage(duration, total_time),
+                    branches: branches
+                        .into_iter()
+                        .map(|(branch, branch_time)| TableRow {
+                            name: branch.to_string(),
+                            time: format_duration(branch_time),
+                            share: percentage(branch_time, duration),
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
     fn generate_time_distribution_observations(&self, summary: &super::DailySummary) -> String {
-        let productive_minutes = summary.productive_time.num_minutes() as f64;
-        let distracted_minutes = summary.distracted_time.num_minutes() as f64;
-        let idle_minutes = summary.idle_time.num_minutes() as f64;
-        let total_minutes = productive_minutes + distracted_minutes + idle_minutes;
+        let total_time = summary.productive_time + summary.distracted_time + summary.idle_time + summary.neutral_time;
         
         let mut observations = String::new();
         
-        if total_minutes == 0.0 {
+        if total_time <= TimeDelta::zero() {
             return "No activity tracked during this session.".to_string();
         }
         
         // Time distribution observations
-        if productive_minutes > 0.0 {
+        if summary.productive_time > TimeDelta::zero() {
             observations.push_str(&format!(
-                "You spent {:.0} minutes on focused activities, which is {:.1}% of your tracked time. ",
-                productive_minutes,
-                (productive_minutes / total_minutes) * 100.0
+                "You spent {} on focused activities, which is {:.1}% of your tracked time. ",
+                format_duration(summary.productive_time),
+                percentage(summary.productive_time, total_time)
             ));
         }
         
         // Distraction observations
-        if distracted_minutes > 0.0 {
+        if summary.distracted_time > TimeDelta::zero() {
             observations.push_str(&format!(
-                "You spent {:.0} minutes on distracting activities ({:.1}% of your time). ",
-                distracted_minutes,
-                (distracted_minutes / total_minutes) * 100.0
+                "You spent {} on distracting activities ({:.1}% of your time). ",
+                format_duration(summary.distracted_time),
+                percentage(summary.distracted_time, total_time)
             ));
         }
         
         // Idle observations
-        if idle_minutes > 0.0 {
+        if summary.idle_time > TimeDelta::zero() {
             observations.push_str(&format!(
-                "You were idle for {:.0} minutes ({:.1}% of your time). ",
-                idle_minutes,
-                (idle_minutes / total_minutes) * 100.0
+                "You were idle for {} ({:.1}% of your time). ",
+                format_duration(summary.idle_time),
+                percentage(summary.idle_time, total_time)
             ));
         }
         
         // Application observations
         if let Some((app, duration)) = summary.application_breakdown.iter()
             .max_by(|a, b| a.1.cmp(b.1)) {
-            if duration.num_minutes() > 0 {
+            if *duration > TimeDelta::zero() {
                 observations.push_str(&format!(
-                    "The application you used most was '{}' for {:.0} minutes. ",
-                    app, duration.num_minutes()
+                    "The application you used most was '{}' for {}. ",
+                    app, format_duration(*duration)
                 ));
             }
         }
         
         // Time distribution rating
-        let time_distribution_score = if total_minutes > 0.0 {
-            let active_time = productive_minutes + distracted_minutes;
-            if active_time > 0.0 {
-                (productive_minutes / active_time) * 100.0
-            } else {
-                100.0
-            }
-        } else {
-            100.0
-        };
-        
-        let rating = self.get_time_distribution_rating(time_distribution_score);
+        let (rating, _) = self.scoring.rate(self.scoring.score_summary(summary));
         observations.push_str(&format!(
             "Your time distribution pattern for this session is categorized as '{}'.",
             rating
@@ -399,4 +417,4 @@ impl ReportGenerator {
         
         observations
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,225 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::DailySummary;
+
+/// How the Time Distribution Score is computed and rated. The score is the
+/// weighted share of active time, minus an optional penalty for switching
+/// applications too often, clamped to 0-100.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringModel {
+    /// Credit per category, from 0 (none) to 1 (full). Active time in
+    /// categories without a weight doesn't count toward the score.
+    pub weights: BTreeMap<String, f64>,
+    /// Rating bands; the one with the highest `min_score` the score reaches
+    /// applies
+    pub ratings: Vec<Rating>,
+    /// Points subtracted per app switch per hour above `free_switches_per_hour`
+    pub switch_penalty: f64,
+    pub free_switches_per_hour: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    pub min_score: f64,
+    pub label: String,
+    pub level: RatingLevel,
+}
+
+/// Controls how a rating is highlighted in reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RatingLevel {
+    High,
+    Medium,
+    Low,
+}
+
+impl RatingLevel {
+    pub fn css_class(self) -> &'static str {
+        match self {
+            RatingLevel::High => "high-distribution",
+            RatingLevel::Medium => "medium-distribution",
+            RatingLevel::Low => "low-distribution",
+        }
+    }
+}
+
+/// Label shown when nothing weighted was tracked
+pub const NO_ACTIVITY_LABEL: &str = "No Activity";
+const NO_ACTIVITY_CLASS: &str = "no-activity";
+
+impl Default for ScoringModel {
+    fn default() -> Self {
+        let rating = |min_score: f64, label: &str, level| Rating {
+            min_score,
+            label: label.to_string(),
+            level,
+        };
+        ScoringModel {
+            weights: BTreeMap::from([
+                ("productive".to_string(), 1.0),
+                ("distraction".to_string(), 0.0),
+            ]),
+            ratings: vec![
+                rating(80.0, "High Focus", RatingLevel::High),
+                rating(60.0, "Moderate Focus", RatingLevel::High),
+                rating(40.0, "Balanced", RatingLevel::Medium),
+                rating(0.0, "Distracted", RatingLevel::Low),
+            ],
+            switch_penalty: 0.0,
+            free_switches_per_hour: 0.0,
+        }
+    }
+}
+
+impl ScoringModel {
+    pub fn validate(&self) -> Result<(), String> {
+        for (category, weight) in &self.weights {
+            if !(0.0..=1.0).contains(weight) {
+                return Err(format!(
+                    "scoring weight for '{}' must be between 0 and 1",
+                    category
+                ));
+            }
+        }
+        if self.ratings.is_empty() {
+            return Err("'scoring.ratings' must not be empty".to_string());
+        }
+        for rating in &self.ratings {
+            if !(0.0..=100.0).contains(&rating.min_score) {
+                return Err(format!(
+                    "rating '{}' has a min_score outside 0-100",
+                    rating.label
+                ));
+            }
+        }
+        if self.switch_penalty < 0.0 || self.free_switches_per_hour < 0.0 {
+            return Err("'scoring.switch_penalty' and 'free_switches_per_hour' must not be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// Scores active time per category, or `None` if no weighted category was
+    /// tracked
+    pub fn score(&self, category_time: &HashMap<String, Duration>, switches_per_hour: f64) -> Option<f64> {
+        let mut weighted_ms = 0.0;
+        let mut total_ms = 0.0;
+        for (category, duration) in category_time {
+            if let Some(weight) = self.weights.get(category) {
+                let ms = duration.num_milliseconds() as f64;
+                weighted_ms += weight * ms;
+                total_ms += ms;
+            }
+        }
+        if total_ms <= 0.0 {
+            return None;
+        }
+
+        let excess_switches = (switches_per_hour - self.free_switches_per_hour).max(0.0);
+        let score = weighted_ms / total_ms * 100.0 - excess_switches * self.switch_penalty;
+        Some(score.clamp(0.0, 100.0))
+    }
+
+    /// Scores a saved day. Summaries written before categories were kept
+    /// have no `category_breakdown`; they are scored from their productive
+    /// and distracted time instead.
+    pub fn score_summary(&self, summary: &DailySummary) -> Option<f64> {
+        if !summary.category_breakdown.is_empty() {
+            return self.score(&summary.category_breakdown, summary.focus_metrics.switches_per_hour);
+        }
+        let category_time = HashMap::from([
+            ("productive".to_string(), summary.productive_time),
+            ("distraction".to_string(), summary.distracted_time),
+            ("neutral".to_string(), summary.neutral_time),
+        ]);
+        self.score(&category_time, summary.focus_metrics.switches_per_hour)
+    }
+
+    /// The rating label and its CSS class
+    pub fn rate(&self, score: Option<f64>) -> (&str, &'static str) {
+        let by_min_score = |a: &&Rating, b: &&Rating| a.min_score.total_cmp(&b.min_score);
+        let rating = score.and_then(|score| {
+            self.ratings
+                .iter()
+                .filter(|rating| score >= rating.min_score)
+                .max_by(by_min_score)
+                // Below every band: use the lowest one
+                .or_else(|| self.ratings.iter().min_by(by_min_score))
+        });
+        match rating {
+            Some(rating) => (&rating.label, rating.level.css_class()),
+            None => (NO_ACTIVITY_LABEL, NO_ACTIVITY_CLASS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_daily_summary, TimeBlock};
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    fn minutes(entries: &[(&str, i64)]) -> HashMap<String, Duration> {
+        entries
+            .iter()
+            .map(|(category, minutes)| (category.to_string(), Duration::minutes(*minutes)))
+            .collect()
+    }
+
+    #[test]
+    fn categories_are_weighted_by_time() {
+        let model = ScoringModel {
+            weights: BTreeMap::from([
+                ("productive".to_string(), 1.0),
+                ("meeting".to_string(), 0.5),
+                ("distraction".to_string(), 0.0),
+            ]),
+            ..ScoringModel::default()
+        };
+        // (60 + 0.5 * 60) / (60 + 60 + 30), ignoring the unweighted neutral time
+        let time = minutes(&[("productive", 60), ("meeting", 60), ("distraction", 30), ("neutral", 600)]);
+        assert_eq!(model.score(&time, 0.0), Some(60.0));
+        assert_eq!(model.rate(Some(60.0)), ("Moderate Focus", "high-distribution"));
+
+        assert_eq!(model.score(&minutes(&[("neutral", 60)]), 0.0), None);
+        assert_eq!(model.rate(None), (NO_ACTIVITY_LABEL, NO_ACTIVITY_CLASS));
+    }
+
+    #[test]
+    fn switching_above_the_free_rate_costs_points() {
+        let model = ScoringModel {
+            switch_penalty: 2.0,
+            free_switches_per_hour: 10.0,
+            ..ScoringModel::default()
+        };
+        let time = minutes(&[("productive", 90), ("distraction", 10)]);
+        assert_eq!(model.score(&time, 10.0), Some(90.0));
+        assert_eq!(model.score(&time, 15.0), Some(80.0));
+        assert_eq!(model.score(&time, 100.0), Some(0.0));
+    }
+
+    #[test]
+    fn summaries_without_categories_fall_back_to_their_totals() {
+        let start = Local.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let blocks: Vec<TimeBlock> = [("productive", 0, 45), ("distraction", 45, 60)]
+            .iter()
+            .map(|(activity_type, from, to)| {
+                let mut block =
+                    TimeBlock::new(start + Duration::minutes(*from), "app".to_string(), activity_type.to_string(), false);
+                block.end_time = start + Duration::minutes(*to);
+                block
+            })
+            .collect();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let mut summary = build_daily_summary(&blocks, date, Duration::zero());
+        let model = ScoringModel::default();
+        assert_eq!(model.score_summary(&summary), Some(75.0));
+
+        // As read from a summary saved before `category_breakdown` existed
+        summary.category_breakdown.clear();
+        assert_eq!(model.score_summary(&summary), Some(75.0));
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::NaiveDate;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::corrections;
+use crate::TimeBlock;
+
+/// Raw blocks are stored one file per day next to the summaries, as
+/// `blocks_<date>.json`
+pub fn blocks_path(data_directory: &str, date: NaiveDate) -> PathBuf {
+    Path::new(data_directory).join(format!("blocks_{}.json", date.format("%Y-%m-%d")))
+}
+
+/// Loads the stored blocks for a day. A day without a file has no blocks.
+pub fn load_blocks(data_directory: &str, date: NaiveDate) -> Result<Vec<TimeBlock>, String> {
+    let path = blocks_path(data_directory, date);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Loads a day's blocks with the user's corrections applied. This is what
+/// summaries and reports are built from.
+pub fn load_effective_blocks(data_directory: &str, date: NaiveDate) -> Result<Vec<TimeBlock>, String> {
+    let blocks = load_blocks(data_directory, date)?;
+    let corrections = corrections::load(data_directory, date)?;
+    Ok(corrections::apply(blocks, &corrections))
+}
+
+pub fn save_blocks(data_directory: &str, date: NaiveDate, blocks: &[TimeBlock]) -> Result<(), String> {
+    let path = blocks_path(data_directory, date);
+    let json = serde_json::to_string_pretty(blocks).map_err(|e| e.to_string())?;
+
+    // Write to a temporary file first so a crash never leaves a truncated day
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Every date from `from` to `to`, inclusive
+pub fn dates_in_range(from: NaiveDate, to: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    from.iter_days().take_while(move |date| *date <= to)
+}
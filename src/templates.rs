@@ -0,0 +1,177 @@
+use chrono::Duration;
+use minijinja::{context, Environment, Error, ErrorKind, Value};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::time_format::format_duration;
+
+/// Built-in templates, compiled into the binary. Templates ending in `.html`
+/// escape every value they interpolate unless it is marked `|safe`.
+const TEMPLATES: [(&str, &str); 3] = [
+    ("daily_report.html", include_str!("../templates/daily_report.html")),
+    ("period_report.html", include_str!("../templates/period_report.html")),
+    ("theme.css", include_str!("../templates/theme.css")),
+];
+
+/// How HTML reports look
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportSettings {
+    pub theme: Theme,
+    /// Directory whose templates replace the built-in ones of the same name
+    pub template_directory: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    /// Follows the light or dark preference of the browser
+    Auto,
+}
+
+impl ReportSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(directory) = &self.template_directory {
+            if !PathBuf::from(directory).is_dir() {
+                return Err(format!("template_directory '{}' is not a directory", directory));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders the named template with `context`. Every template also gets
+/// `theme` and the `duration` filter.
+pub fn render<S: Serialize>(name: &str, context: S, settings: &ReportSettings) -> Result<String, String> {
+    let directory = settings.template_directory.clone().map(PathBuf::from);
+    let mut environment = Environment::new();
+    environment.set_loader(move |name| {
+        if let Some(directory) = &directory {
+            match fs::read_to_string(directory.join(name)) {
+                Ok(source) => return Ok(Some(source)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidOperation,
+                        format!("Failed to read template {}", name),
+                    )
+                    .with_source(e))
+                }
+            }
+        }
+        Ok(TEMPLATES
+            .iter()
+            .find(|(template_name, _)| *template_name == name)
+            .map(|(_, source)| source.to_string()))
+    });
+    environment.add_filter("duration", duration_filter);
+
+    let context = context! { theme => settings.theme, ..Value::from_serialize(&context) };
+    environment
+        .get_template(name)
+        .and_then(|template| template.render(context))
+        .map_err(|e| format!("Failed to render {}: {:#}", name, e))
+}
+
+/// `{{ seconds|duration }}` formats a number of seconds, or a serialized
+/// duration (`[seconds, nanoseconds]`), like the rest of the report
+fn duration_filter(value: Value) -> Result<String, Error> {
+    let seconds = match value.get_item_by_index(0) {
+        Ok(first) if !first.is_undefined() => first,
+        _ => value,
+    };
+    let seconds = f64::try_from(seconds)?;
+    Ok(format_duration(Duration::milliseconds((seconds * 1000.0) as i64)))
+}
+
+/// Escapes text for markup built outside the templates, such as the inline
+/// SVG charts. Window titles and category names are arbitrary text, so they
+/// must not be able to break out of it.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::MeetingSummary;
+    use crate::report_generator::ReportGenerator;
+    use crate::scoring::ScoringModel;
+    use crate::{build_daily_summary, TimeBlock};
+    use chrono::{Local, TimeZone};
+
+    const HOSTILE: &str = r#"<script>alert("Tom & Jerry's")</script>"#;
+
+    #[test]
+    fn markup_characters_are_escaped() {
+        assert_eq!(
+            escape(HOSTILE),
+            "&lt;script&gt;alert(&quot;Tom &amp; Jerry&#39;s&quot;)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn hostile_apps_and_titles_stay_text_in_the_daily_report() {
+        let start = Local.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+        let mut block = TimeBlock::new(start, HOSTILE.to_string(), HOSTILE.to_string(), false);
+        block.window_title = Some(HOSTILE.to_string());
+        block.end_time = start + Duration::minutes(30);
+        let blocks = vec![block];
+        let summary = build_daily_summary(&blocks, start.date_naive(), Duration::zero());
+
+        let directory = std::env::temp_dir().join(format!("timesense-templates-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let generator = ReportGenerator::new(
+            directory.to_string_lossy().into_owned(),
+            ScoringModel::default(),
+            ReportSettings::default(),
+        );
+        generator.generate_report(&summary, &blocks, &[], &MeetingSummary::default());
+        let html = fs::read_to_string(directory.join("report_2026-10-19.html")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("Jerry's"));
+        assert!(!html.contains(r#"alert(""#));
+        // In the timeline tooltip and the legend, and in the tables
+        assert!(html.contains(&escape(HOSTILE)));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn the_duration_filter_takes_seconds_or_durations() {
+        let directory = std::env::temp_dir().join(format!("timesense-duration-filter-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("durations.txt"),
+            "{{ seconds|duration }} / {{ fraction|duration }} / {{ length|duration }}",
+        )
+        .unwrap();
+        let settings = ReportSettings {
+            template_directory: Some(directory.to_string_lossy().into_owned()),
+            ..ReportSettings::default()
+        };
+        let context = context! {
+            seconds => 3725,
+            fraction => 89.4,
+            length => Duration::seconds(150),
+        };
+        let rendered = render("durations.txt", context, &settings);
+
+        fs::write(directory.join("durations.txt"), "{{ name|duration }}").unwrap();
+        let not_a_number = render("durations.txt", context! { name => "editor" }, &settings);
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(rendered.unwrap(), "1h 2m / 1m 29s / 2m 30s");
+        assert!(not_a_number.is_err());
+    }
+}
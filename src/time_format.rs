@@ -0,0 +1,115 @@
+use chrono::Duration as TimeDelta;
+
+/// Formats a duration for people: "2h 14m", "14m 5s", "45s". The two most
+/// significant units are shown, rounded to the nearest second.
+pub fn format_duration(duration: TimeDelta) -> String {
+    let total_seconds = (duration.num_milliseconds() as f64 / 1000.0).round() as i64;
+    let sign = if total_seconds < 0 { "-" } else { "" };
+    let total_seconds = total_seconds.abs();
+
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+
+    let formatted = if hours > 0 {
+        if minutes > 0 {
+            format!("{}h {}m", hours, minutes)
+        } else {
+            format!("{}h", hours)
+        }
+    } else if minutes > 0 {
+        if seconds > 0 {
+            format!("{}m {}s", minutes, seconds)
+        } else {
+            format!("{}m", minutes)
+        }
+    } else {
+        format!("{}s", seconds)
+    };
+
+    format!("{}{}", sign, formatted)
+}
+
+/// `part` as a percentage of `total`, computed from exact durations
+pub fn percentage(part: TimeDelta, total: TimeDelta) -> f64 {
+    let total_ms = total.num_milliseconds();
+    if total_ms > 0 {
+        part.num_milliseconds() as f64 / total_ms as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Parses a duration as people write it: "30m", "1h30m", "1h 30m", "90s".
+/// A bare number is minutes.
+pub fn parse_duration(text: &str) -> Result<TimeDelta, String> {
+    let invalid = || format!("Invalid duration (e.g. 30m, 1h30m or 90s): {}", text);
+    let text = text.trim();
+    if let Ok(minutes) = text.parse::<u32>() {
+        return if minutes > 0 { Ok(TimeDelta::minutes(minutes.into())) } else { Err(invalid()) };
+    }
+
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: i64 = number.parse().map_err(|_| invalid())?;
+        let part = match c {
+            'h' => TimeDelta::try_hours(value),
+            'm' => TimeDelta::try_minutes(value),
+            's' => TimeDelta::try_seconds(value),
+            _ => return Err(invalid()),
+        };
+        total = part.and_then(|part| total.checked_add(&part)).ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() || total <= TimeDelta::zero() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_duration_rounds_to_the_second() {
+        assert_eq!(format_duration(TimeDelta::milliseconds(499)), "0s");
+        assert_eq!(format_duration(TimeDelta::milliseconds(500)), "1s");
+        assert_eq!(format_duration(TimeDelta::milliseconds(59_500)), "1m");
+        assert_eq!(format_duration(TimeDelta::milliseconds(89_400)), "1m 29s");
+        assert_eq!(format_duration(TimeDelta::milliseconds(7_199_600)), "2h");
+        assert_eq!(format_duration(TimeDelta::seconds(3_725)), "1h 2m");
+        assert_eq!(format_duration(TimeDelta::seconds(-30)), "-30s");
+    }
+
+    #[test]
+    fn percentage_of_nothing_is_zero() {
+        assert_eq!(percentage(TimeDelta::minutes(5), TimeDelta::zero()), 0.0);
+        assert_eq!(percentage(TimeDelta::zero(), TimeDelta::zero()), 0.0);
+        assert_eq!(percentage(TimeDelta::seconds(15), TimeDelta::minutes(1)), 25.0);
+    }
+
+    #[test]
+    fn parse_duration_forms() {
+        assert_eq!(parse_duration("30m"), Ok(TimeDelta::minutes(30)));
+        assert_eq!(parse_duration("1h 30m"), Ok(TimeDelta::minutes(90)));
+        assert_eq!(parse_duration("90s"), Ok(TimeDelta::seconds(90)));
+        assert_eq!(parse_duration("45"), Ok(TimeDelta::minutes(45)));
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn parse_duration_out_of_range_is_an_error() {
+        assert!(parse_duration("3000000000000h").is_err());
+        assert!(parse_duration("9223372036854775807s").is_err());
+        assert!(parse_duration("99999999999999999999h").is_err());
+        assert!(parse_duration("2562047788015h 2562047788015h").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{DateTime, Duration, Local, Timelike};
+
+use crate::templates::escape;
+use crate::time_format::format_duration;
+use crate::TimeBlock;
+
+const LABEL_WIDTH: f64 = 56.0;
+const TRACK_WIDTH: f64 = 900.0;
+const AXIS_HEIGHT: f64 = 18.0;
+const ROW_HEIGHT: f64 = 20.0;
+const ROW_GAP: f64 = 6.0;
+const LEGEND_HEIGHT: f64 = 28.0;
+
+/// Colors used for the well-known categories; other categories get a stable
+/// color from `EXTRA_COLORS`
+const IDLE_COLOR: &str = "#9E9E9E";
+const EXTRA_COLORS: [&str; 6] = ["#FF9800", "#9C27B0", "#009688", "#795548", "#3F51B5", "#CDDC39"];
+
+pub fn category_color(category: &str, idle: bool) -> &'static str {
+    if idle {
+        return IDLE_COLOR;
+    }
+    match category {
+        "productive" => "#4CAF50",
+        "distraction" => "#F44336",
+        "neutral" => "#2196F3",
+        other => {
+            let hash = other.bytes().fold(0usize, |acc, b| acc.wrapping_mul(31).wrapping_add(b as usize));
+            EXTRA_COLORS[hash % EXTRA_COLORS.len()]
+        }
+    }
+}
+
+/// Renders the blocks as an inline SVG with one row per hour. Each segment
+/// carries a `<title>` so browsers show the app, window title and duration on
+/// hover without any scripting.
+pub fn render_timeline_svg(blocks: &[TimeBlock]) -> String {
+    let (first, last) = match (
+        blocks.iter().map(|block| block.start_time).min(),
+        blocks.iter().map(|block| block.end_time).max(),
+    ) {
+        (Some(first), Some(last)) if last > first => (first, last),
+        _ => return "<p>No activity recorded.</p>".to_string(),
+    };
+
+    let mut hours: Vec<DateTime<Local>> = Vec::new();
+    let mut hour_start = start_of_hour(first);
+    while hour_start < last {
+        hours.push(hour_start);
+        hour_start += Duration::hours(1);
+    }
+
+    let width = LABEL_WIDTH + TRACK_WIDTH;
+    let height = AXIS_HEIGHT + hours.len() as f64 * (ROW_HEIGHT + ROW_GAP) + LEGEND_HEIGHT;
+    let mut svg = format!(
+        r#"<svg class="timeline" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="100%" role="img" aria-label="Timeline of the day">"#,
+        w = width,
+        h = height
+    );
+
+    // Minute axis
+    for minute in [0, 15, 30, 45] {
+        svg.push_str(&format!(
+            r##"<text x="{:.1}" y="12" class="axis">:{:02}</text>"##,
+            LABEL_WIDTH + minute as f64 / 60.0 * TRACK_WIDTH,
+            minute
+        ));
+    }
+
+    for (row, hour_start) in hours.iter().enumerate() {
+        let hour_end = *hour_start + Duration::hours(1);
+        let y = AXIS_HEIGHT + row as f64 * (ROW_HEIGHT + ROW_GAP);
+
+        svg.push_str(&format!(
+            r##"<text x="0" y="{:.1}" class="hour">{}</text><rect x="{}" y="{:.1}" width="{}" height="{}" fill="#f0f0f0"/>"##,
+            y + ROW_HEIGHT * 0.75,
+            hour_start.format("%H:00"),
+            LABEL_WIDTH,
+            y,
+            TRACK_WIDTH,
+            ROW_HEIGHT
+        ));
+
+        for block in blocks {
+            let segment_start = block.start_time.max(*hour_start);
+            let segment_end = block.end_time.min(hour_end);
+            if segment_end <= segment_start {
+                continue;
+            }
+
+            let x = LABEL_WIDTH + seconds_between(*hour_start, segment_start) / 3600.0 * TRACK_WIDTH;
+            let segment_width = seconds_between(segment_start, segment_end) / 3600.0 * TRACK_WIDTH;
+            svg.push_str(&format!(
+                r#"<rect x="{:.2}" y="{:.1}" width="{:.2}" height="{}" fill="{}"><title>{}</title></rect>"#,
+                x,
+                y,
+                segment_width,
+                ROW_HEIGHT,
+                category_color(&block.activity_type, block.idle),
+                escape(&tooltip(block))
+            ));
+        }
+    }
+
+    // Legend with the categories that appear in the day
+    let mut legend: Vec<(String, &str)> = Vec::new();
+    for block in blocks {
+        let label = if block.idle { "idle".to_string() } else { block.activity_type.clone() };
+        if !legend.iter().any(|(existing, _)| *existing == label) {
+            legend.push((label, category_color(&block.activity_type, block.idle)));
+        }
+    }
+    let legend_y = height - LEGEND_HEIGHT + 10.0;
+    for (i, (label, color)) in legend.iter().enumerate() {
+        let x = LABEL_WIDTH + i as f64 * 130.0;
+        svg.push_str(&format!(
+            r#"<rect x="{:.1}" y="{:.1}" width="12" height="12" fill="{}"/><text x="{:.1}" y="{:.1}" class="legend">{}</text>"#,
+            x,
+            legend_y,
+            color,
+            x + 18.0,
+            legend_y + 11.0,
+            escape(label)
+        ));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn tooltip(block: &TimeBlock) -> String {
+    let mut lines = vec![block.application.clone()];
+    if let Some(title) = &block.window_title {
+        lines.push(title.clone());
+    }
+    lines.push(format!(
+        "{} - {} ({}), {}",
+        block.start_time.format("%H:%M:%S"),
+        block.end_time.format("%H:%M:%S"),
+        format_duration(block.end_time.signed_duration_since(block.start_time)),
+        if block.idle { "idle" } else { &block.activity_type }
+    ));
+    lines.join("\n")
+}
+
+pub fn start_of_hour(time: DateTime<Local>) -> DateTime<Local> {
+    time - Duration::minutes(time.minute() as i64)
+        - Duration::seconds(time.second() as i64)
+        - Duration::nanoseconds(time.nanosecond() as i64)
+}
+
+fn seconds_between(from: DateTime<Local>, to: DateTime<Local>) -> f64 {
+    to.signed_duration_since(from).num_milliseconds() as f64 / 1000.0
+}
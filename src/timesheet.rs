@@ -0,0 +1,402 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::cli::TimesheetTool;
+use crate::config::Config;
+use crate::export;
+use crate::time_format::format_duration;
+use crate::TimeBlock;
+
+/// How blocks are rolled up into timesheet entries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimesheetConfig {
+    /// Project for blocks of these categories that have no project of their
+    /// own; blocks with neither are left out
+    pub category_projects: BTreeMap<String, String>,
+    /// Client of each project
+    pub clients: BTreeMap<String, String>,
+    /// Entry lengths are rounded to a multiple of this (0 turns rounding off)
+    pub round_to_minutes: u64,
+    pub rounding: Rounding,
+    /// Entries shorter than this (before rounding) are dropped
+    pub min_entry_minutes: u64,
+    /// Consecutive entries of the same project and category separated by at
+    /// most this much are merged, absorbing the gap
+    pub merge_gap_minutes: u64,
+    pub billable: bool,
+    /// Person the entries belong to, for tools that need one
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+    Up,
+    #[default]
+    Nearest,
+    Down,
+}
+
+impl Default for TimesheetConfig {
+    fn default() -> Self {
+        TimesheetConfig {
+            category_projects: BTreeMap::new(),
+            clients: BTreeMap::new(),
+            round_to_minutes: 15,
+            rounding: Rounding::Nearest,
+            min_entry_minutes: 5,
+            merge_gap_minutes: 5,
+            billable: true,
+            email: None,
+            first_name: None,
+            last_name: None,
+        }
+    }
+}
+
+/// One line of a timesheet
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// Logical day the entry belongs to
+    pub date: NaiveDate,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub project: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+impl Entry {
+    fn duration(&self) -> Duration {
+        self.end.signed_duration_since(self.start)
+    }
+
+    /// The block notes, or the category when there are none
+    fn description(&self) -> String {
+        if self.notes.is_empty() {
+            self.category.clone()
+        } else {
+            self.notes.join("; ")
+        }
+    }
+}
+
+impl TimesheetConfig {
+    fn project_of(&self, block: &TimeBlock) -> Option<String> {
+        if block.idle {
+            return None;
+        }
+        block
+            .project
+            .clone()
+            .or_else(|| self.category_projects.get(&block.activity_type).cloned())
+    }
+
+    fn round(&self, duration: Duration) -> Duration {
+        if self.round_to_minutes == 0 {
+            return duration;
+        }
+        let step = self.round_to_minutes as i64 * 60;
+        let seconds = duration.num_seconds();
+        let steps = match self.rounding {
+            Rounding::Up => (seconds + step - 1) / step,
+            Rounding::Nearest => (seconds + step / 2) / step,
+            Rounding::Down => seconds / step,
+        };
+        Duration::seconds(steps * step)
+    }
+
+    /// Rolls one day's blocks up into entries: merges runs of the same project
+    /// and category, drops short entries and rounds the rest. Rounding moves
+    /// the end of an entry; when that runs into the next entry, the next one
+    /// starts later so that entries never overlap.
+    pub fn entries(&self, date: NaiveDate, blocks: &[TimeBlock]) -> Vec<Entry> {
+        let merge_gap = Duration::minutes(self.merge_gap_minutes as i64);
+        let mut entries: Vec<Entry> = Vec::new();
+
+        for block in blocks {
+            let Some(project) = self.project_of(block) else {
+                continue;
+            };
+            if let Some(last) = entries.last_mut() {
+                if last.project == project
+                    && last.category == block.activity_type
+                    && block.start_time.signed_duration_since(last.end) <= merge_gap
+                {
+                    last.end = last.end.max(block.end_time);
+                    extend_unique(&mut last.tags, &block.tags);
+                    extend_unique(&mut last.notes, &block.notes);
+                    continue;
+                }
+            }
+            entries.push(Entry {
+                date,
+                start: block.start_time,
+                end: block.end_time,
+                project,
+                category: block.activity_type.clone(),
+                tags: block.tags.clone(),
+                notes: block.notes.clone(),
+            });
+        }
+
+        let min_length = Duration::minutes(self.min_entry_minutes as i64);
+        let mut previous_end: Option<DateTime<Local>> = None;
+        entries
+            .into_iter()
+            .filter(|entry| entry.duration() >= min_length)
+            .filter_map(|mut entry| {
+                let rounded = self.round(entry.duration());
+                if rounded <= Duration::zero() {
+                    return None;
+                }
+                if let Some(previous_end) = previous_end {
+                    entry.start = entry.start.max(previous_end);
+                }
+                entry.end = entry.start + rounded;
+                previous_end = Some(entry.end);
+                Some(entry)
+            })
+            .collect()
+    }
+}
+
+fn extend_unique(target: &mut Vec<String>, items: &[String]) {
+    for item in items {
+        if !target.contains(item) {
+            target.push(item.clone());
+        }
+    }
+}
+
+/// "01:30:00"
+fn clock_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "Yes"
+    } else {
+        "No"
+    }
+}
+
+/// Header and rows in the CSV import layout of `tool`
+fn rows(settings: &TimesheetConfig, tool: TimesheetTool, entries: &[Entry]) -> (Vec<&'static str>, Vec<Vec<String>>) {
+    let client = |entry: &Entry| settings.clients.get(&entry.project).cloned().unwrap_or_default();
+    let email = settings.email.clone().unwrap_or_default();
+
+    match tool {
+        TimesheetTool::Toggl => (
+            vec![
+                "Email",
+                "Start date",
+                "Start time",
+                "Duration",
+                "Project",
+                "Client",
+                "Description",
+                "Tags",
+                "Billable",
+            ],
+            entries
+                .iter()
+                .map(|entry| {
+                    vec![
+                        email.clone(),
+                        entry.start.format("%Y-%m-%d").to_string(),
+                        entry.start.format("%H:%M:%S").to_string(),
+                        clock_duration(entry.duration()),
+                        entry.project.clone(),
+                        client(entry),
+                        entry.description(),
+                        entry.tags.join(", "),
+                        yes_no(settings.billable).to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        TimesheetTool::Clockify => (
+            vec![
+                "Project",
+                "Client",
+                "Description",
+                "Email",
+                "Tags",
+                "Billable",
+                "Start Date",
+                "Start Time",
+                "End Date",
+                "End Time",
+            ],
+            entries
+                .iter()
+                .map(|entry| {
+                    vec![
+                        entry.project.clone(),
+                        client(entry),
+                        entry.description(),
+                        email.clone(),
+                        entry.tags.join(", "),
+                        yes_no(settings.billable).to_string(),
+                        entry.start.format("%Y-%m-%d").to_string(),
+                        entry.start.format("%H:%M").to_string(),
+                        entry.end.format("%Y-%m-%d").to_string(),
+                        entry.end.format("%H:%M").to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        TimesheetTool::Harvest => (
+            vec!["Date", "Client", "Project", "Task", "Notes", "Hours", "First name", "Last name"],
+            entries
+                .iter()
+                .map(|entry| {
+                    vec![
+                        entry.date.to_string(),
+                        client(entry),
+                        entry.project.clone(),
+                        entry.category.clone(),
+                        entry.notes.join("; "),
+                        format!("{:.2}", entry.duration().num_seconds() as f64 / 3600.0),
+                        settings.first_name.clone().unwrap_or_default(),
+                        settings.last_name.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Writes the timesheet for the range, using `settings` instead of the
+/// configured ones (the command line can override rounding and merging)
+pub fn run(
+    config: &Config,
+    settings: &TimesheetConfig,
+    from: NaiveDate,
+    to: NaiveDate,
+    tool: TimesheetTool,
+    output: Option<&Path>,
+) -> Result<(), String> {
+    let entries: Vec<Entry> = export::load_days(config, from, to)?
+        .iter()
+        .flat_map(|(date, blocks)| settings.entries(*date, blocks))
+        .collect();
+
+    let (header, rows) = rows(settings, tool, &entries);
+    export::write_output(&export::to_csv(&header, &rows)?, output)?;
+
+    let total = entries
+        .iter()
+        .fold(Duration::zero(), |total, entry| total + entry.duration());
+    eprintln!("{} entries, {} in total", entries.len(), format_duration(total));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 19, hour, minute, 0).unwrap()
+    }
+
+    fn block(start: DateTime<Local>, end: DateTime<Local>, project: &str, note: &str) -> TimeBlock {
+        let mut block = TimeBlock::new(start, "editor".to_string(), "productive".to_string(), false);
+        block.end_time = end;
+        block.project = Some(project.to_string());
+        block.notes = vec![note.to_string()];
+        block
+    }
+
+    fn spans(entries: &[Entry]) -> Vec<(DateTime<Local>, DateTime<Local>, &str)> {
+        entries.iter().map(|entry| (entry.start, entry.end, entry.project.as_str())).collect()
+    }
+
+    #[test]
+    fn lengths_are_rounded_by_the_configured_rule() {
+        let settings = |rounding| TimesheetConfig {
+            round_to_minutes: 15,
+            rounding,
+            ..TimesheetConfig::default()
+        };
+        let minutes = |settings: &TimesheetConfig, minutes| settings.round(Duration::minutes(minutes)).num_minutes();
+        assert_eq!(minutes(&settings(Rounding::Nearest), 22), 15);
+        assert_eq!(minutes(&settings(Rounding::Nearest), 23), 30);
+        assert_eq!(minutes(&settings(Rounding::Up), 16), 30);
+        assert_eq!(minutes(&settings(Rounding::Up), 15), 15);
+        assert_eq!(minutes(&settings(Rounding::Down), 29), 15);
+
+        let exact = TimesheetConfig {
+            round_to_minutes: 0,
+            ..TimesheetConfig::default()
+        };
+        assert_eq!(minutes(&exact, 22), 22);
+    }
+
+    #[test]
+    fn adjacent_blocks_of_a_project_are_merged() {
+        let settings = TimesheetConfig {
+            round_to_minutes: 0,
+            ..TimesheetConfig::default()
+        };
+        let mut idle = block(at(10, 30), at(11, 0), "website", "away");
+        idle.idle = true;
+        let blocks = [
+            block(at(9, 0), at(9, 20), "website", "layout"),
+            // A gap of exactly merge_gap_minutes is absorbed
+            block(at(9, 25), at(9, 40), "website", "layout"),
+            block(at(9, 40), at(10, 0), "website", "fonts"),
+            // Past the gap
+            block(at(10, 6), at(10, 30), "website", "fonts"),
+            idle,
+            block(at(11, 0), at(11, 30), "api", "tests"),
+            // Too short to be an entry
+            block(at(11, 30), at(11, 34), "website", "typo"),
+        ];
+        let entries = settings.entries(at(0, 0).date_naive(), &blocks);
+        assert_eq!(
+            spans(&entries),
+            vec![
+                (at(9, 0), at(10, 0), "website"),
+                (at(10, 6), at(10, 30), "website"),
+                (at(11, 0), at(11, 30), "api"),
+            ]
+        );
+        assert_eq!(entries[0].notes, vec!["layout", "fonts"]);
+        assert_eq!(entries[0].description(), "layout; fonts");
+    }
+
+    #[test]
+    fn rounded_entries_do_not_overlap() {
+        let settings = TimesheetConfig {
+            round_to_minutes: 15,
+            rounding: Rounding::Up,
+            merge_gap_minutes: 0,
+            ..TimesheetConfig::default()
+        };
+        let blocks = [
+            block(at(9, 0), at(9, 10), "website", "layout"),
+            block(at(9, 10), at(9, 20), "api", "tests"),
+            block(at(9, 20), at(10, 0), "website", "fonts"),
+        ];
+        let entries = settings.entries(at(0, 0).date_naive(), &blocks);
+        assert_eq!(
+            spans(&entries),
+            vec![
+                (at(9, 0), at(9, 15), "website"),
+                (at(9, 15), at(9, 30), "api"),
+                (at(9, 30), at(10, 15), "website"),
+            ]
+        );
+    }
+}
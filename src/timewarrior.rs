@@ -0,0 +1,367 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::corrections::{self, Correction};
+use crate::day_boundary;
+use crate::time_format::format_duration;
+use crate::TimeBlock;
+
+/// How Timewarrior intervals are imported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimewarriorConfig {
+    /// TimeSense project for each Timewarrior tag. Intervals without a mapped
+    /// tag use their first tag as the project.
+    pub projects: BTreeMap<String, String>,
+    /// Category of imported intervals
+    pub category: String,
+}
+
+impl Default for TimewarriorConfig {
+    fn default() -> Self {
+        TimewarriorConfig {
+            projects: BTreeMap::new(),
+            category: "productive".to_string(),
+        }
+    }
+}
+
+/// One closed interval of a Timewarrior data file
+#[derive(Debug, Clone)]
+struct Interval {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+
+/// Parses a data file line: `inc <start> [- <end>] [# <tags> [# <annotation>]]`,
+/// where an annotation without tags is written `## <annotation>`. Open
+/// intervals (still running) and other lines return `None`.
+fn parse_line(line: &str) -> Result<Option<Interval>, String> {
+    let invalid = || format!("Invalid Timewarrior line: {}", line);
+    let tokens = tokenize(line.trim());
+    let mut tokens = tokens.iter();
+    if tokens.next().map(|(token, _)| token.as_str()) != Some("inc") {
+        return Ok(None);
+    }
+    let start = match tokens.next() {
+        Some((token, false)) => parse_time(token).ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+    };
+    let end = match tokens.next() {
+        Some((dash, false)) if dash == "-" => match tokens.next() {
+            Some((token, false)) => parse_time(token).ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        },
+        _ => return Ok(None),
+    };
+
+    let mut tags = Vec::new();
+    let mut annotation: Option<Vec<String>> = None;
+    let mut separators = 0;
+    for (token, quoted) in tokens {
+        if token == "#" && !quoted && separators < 2 {
+            separators += 1;
+            if separators == 2 {
+                annotation = Some(Vec::new());
+            }
+            continue;
+        }
+        if token == "##" && !quoted && separators == 0 {
+            separators = 2;
+            annotation = Some(Vec::new());
+            continue;
+        }
+        match annotation.as_mut() {
+            Some(words) => words.push(token.clone()),
+            None if separators == 1 => tags.push(token.clone()),
+            None => return Err(invalid()),
+        }
+    }
+
+    Ok(Some(Interval {
+        start,
+        end,
+        tags,
+        annotation: annotation.map(|words| words.join(" ")).filter(|text| !text.is_empty()),
+    }))
+}
+
+/// Splits on whitespace, keeping double-quoted words (with `\"` escapes)
+/// together. Each token says whether it was quoted.
+fn tokenize(line: &str) -> Vec<(String, bool)> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c == '"' {
+            let mut token = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => token.extend(chars.next()),
+                    '"' => break,
+                    _ => token.push(c),
+                }
+            }
+            tokens.push((token, true));
+        } else {
+            let mut token = c.to_string();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                token.push(c);
+            }
+            tokens.push((token, false));
+        }
+    }
+    tokens
+}
+
+fn parse_time(token: &str) -> Option<DateTime<Local>> {
+    let time = NaiveDateTime::parse_from_str(token.strip_suffix('Z')?, "%Y%m%dT%H%M%S").ok()?;
+    Some(Utc.from_utc_datetime(&time).with_timezone(&Local))
+}
+
+fn format_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Quotes a tag or annotation if Timewarrior would
+fn quote(text: &str) -> String {
+    if !text.is_empty() && !text.contains(|c: char| c.is_whitespace() || c == '"' || c == '#') {
+        return text.to_string();
+    }
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl TimewarriorConfig {
+    /// The manual block an interval becomes: a mapped tag (or the first tag)
+    /// is the project and the remaining tags stay tags
+    fn to_block(&self, interval: &Interval) -> TimeBlock {
+        let mapped = interval
+            .tags
+            .iter()
+            .find_map(|tag| self.projects.get(tag).map(|project| (tag, project.clone())));
+        let (project_tag, project) = match (mapped, interval.tags.first()) {
+            (Some((tag, project)), _) => (Some(tag), Some(project)),
+            (None, Some(tag)) => (Some(tag), Some(tag.clone())),
+            (None, None) => (None, None),
+        };
+        let tags: Vec<String> = interval
+            .tags
+            .iter()
+            .filter(|tag| Some(*tag) != project_tag)
+            .cloned()
+            .collect();
+        let label = match (&interval.annotation, tags.is_empty()) {
+            (Some(annotation), _) => annotation.clone(),
+            (None, false) => tags.join(" "),
+            (None, true) => project.clone().unwrap_or_else(|| "timewarrior".to_string()),
+        };
+
+        let mut block = TimeBlock::new(interval.start, label, self.category.clone(), false);
+        block.end_time = interval.end;
+        block.project = project;
+        block.tags = tags;
+        block.notes = interval.annotation.iter().cloned().collect();
+        block.manual = true;
+        block
+    }
+}
+
+/// Imports the closed intervals of a Timewarrior data file (e.g.
+/// `~/.timewarrior/data/2026-10.data`) as manual blocks, which replace
+/// whatever was tracked at the same time. Intervals imported before are
+/// skipped.
+pub fn import(config: &Config, path: &Path, dry_run: bool) -> Result<(), String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut blocks = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        match parse_line(line) {
+            Ok(Some(interval)) => blocks.push(config.timewarrior.to_block(&interval)),
+            Ok(None) => {}
+            Err(e) => println!("Skipping line {}: {}", number + 1, e),
+        }
+    }
+    if blocks.is_empty() {
+        return Err(format!("{} contains no closed Timewarrior intervals", path.display()));
+    }
+
+    let mut imported = 0;
+    for (date, blocks) in day_boundary::split_by_day(blocks, config.day_start) {
+        let mut day_corrections = corrections::load(&config.data_directory, date)?;
+        let mut added = Vec::new();
+        for block in blocks {
+            let duplicate = day_corrections.iter().any(|correction| {
+                matches!(correction, Correction::Manual { start, end, label, .. }
+                    if *start == block.start_time && *end == block.end_time && *label == block.application)
+            });
+            if !duplicate {
+                added.push(block);
+            }
+        }
+        if added.is_empty() {
+            println!("{}: already imported", date);
+            continue;
+        }
+
+        let total = added.iter().fold(Duration::zero(), |total, block| {
+            total + block.end_time.signed_duration_since(block.start_time)
+        });
+        println!("{}: {} interval(s), {}", date, added.len(), format_duration(total));
+        imported += 1;
+
+        day_corrections.extend(added.into_iter().map(|block| Correction::Manual {
+            start: block.start_time,
+            end: block.end_time,
+            label: block.application,
+            category: block.activity_type,
+            project: block.project,
+            tags: block.tags,
+            note: block.notes.into_iter().next(),
+        }));
+        if !dry_run {
+            corrections::save(&config.data_directory, date, &day_corrections)?;
+            crate::regenerate_day(config, date)?;
+        }
+    }
+
+    println!("{} {} day(s).", if dry_run { "Would import" } else { "Imported" }, imported);
+    if dry_run {
+        println!("Dry run: nothing was written.");
+    }
+    Ok(())
+}
+
+/// Writes active, non-empty blocks as Timewarrior data file lines. Tags are the project,
+/// the category and the block's tags; the annotation is the meeting, the
+/// notes or else the application.
+pub fn export(days: &[(NaiveDate, Vec<TimeBlock>)]) -> String {
+    let mut lines = String::new();
+    let blocks = days.iter().flat_map(|(_, blocks)| blocks);
+    for block in blocks.filter(|block| !block.idle && block.end_time > block.start_time) {
+        let mut tags: Vec<&str> = block.project.iter().map(String::as_str).collect();
+        tags.push(&block.activity_type);
+        tags.extend(block.tags.iter().map(String::as_str));
+        let annotation = match (&block.meeting, block.notes.is_empty()) {
+            (Some(meeting), _) => meeting.clone(),
+            (None, false) => block.notes.join("; "),
+            (None, true) => block.application.clone(),
+        };
+
+        let tags: Vec<String> = tags.iter().map(|tag| quote(tag)).collect();
+        lines.push_str(&format!(
+            "inc {} - {} # {} # {}\n",
+            format_time(block.start_time),
+            format_time(block.end_time),
+            tags.join(" "),
+            quote(&annotation)
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text: &str) -> DateTime<Local> {
+        parse_time(text).unwrap()
+    }
+
+    fn interval(line: &str) -> Interval {
+        parse_line(line).unwrap().unwrap()
+    }
+
+    #[test]
+    fn tokenize_keeps_quoted_words_together() {
+        assert_eq!(
+            tokenize(r##"inc 20261019T090000Z # "two words" plain"##),
+            vec![
+                ("inc".to_string(), false),
+                ("20261019T090000Z".to_string(), false),
+                ("#".to_string(), false),
+                ("two words".to_string(), true),
+                ("plain".to_string(), false),
+            ]
+        );
+        assert_eq!(
+            tokenize(r##""say \"hi\"" "back\\slash" "#""##),
+            vec![
+                (r#"say "hi""#.to_string(), true),
+                (r"back\slash".to_string(), true),
+                ("#".to_string(), true),
+            ]
+        );
+        assert_eq!(tokenize("  ##  x "), vec![("##".to_string(), false), ("x".to_string(), false)]);
+    }
+
+    #[test]
+    fn intervals_with_tags_and_annotation() {
+        let parsed = interval(r##"inc 20261019T090000Z - 20261019T103000Z # acme "code review" # "Reviewed \"parser\" PR""##);
+        assert_eq!(parsed.start, utc("20261019T090000Z"));
+        assert_eq!(parsed.end, utc("20261019T103000Z"));
+        assert_eq!(parsed.tags, vec!["acme", "code review"]);
+        assert_eq!(parsed.annotation.as_deref(), Some(r##"Reviewed "parser" PR"##));
+    }
+
+    #[test]
+    fn intervals_without_annotation_or_tags() {
+        let tags_only = interval("inc 20261019T090000Z - 20261019T100000Z # acme");
+        assert_eq!(tags_only.tags, vec!["acme"]);
+        assert_eq!(tags_only.annotation, None);
+
+        let bare = interval("inc 20261019T090000Z - 20261019T100000Z");
+        assert!(bare.tags.is_empty());
+        assert_eq!(bare.annotation, None);
+
+        let annotated = interval(r##"inc 20261019T090000Z - 20261019T100000Z ## "Sketching the design""##);
+        assert!(annotated.tags.is_empty());
+        assert_eq!(annotated.annotation.as_deref(), Some("Sketching the design"));
+
+        let spaced = interval(r##"inc 20261019T090000Z - 20261019T100000Z # # "Same, spaced out""##);
+        assert!(spaced.tags.is_empty());
+        assert_eq!(spaced.annotation.as_deref(), Some("Same, spaced out"));
+    }
+
+    #[test]
+    fn quoted_hashes_are_text() {
+        let parsed = interval(r###"inc 20261019T090000Z - 20261019T100000Z # "#" "##" # "issue #12""###);
+        assert_eq!(parsed.tags, vec!["#", "##"]);
+        assert_eq!(parsed.annotation.as_deref(), Some("issue #12"));
+    }
+
+    #[test]
+    fn open_intervals_and_other_lines_are_skipped() {
+        assert!(parse_line("inc 20261019T090000Z # acme").unwrap().is_none());
+        assert!(parse_line("inc 20261019T090000Z").unwrap().is_none());
+        assert!(parse_line("").unwrap().is_none());
+        assert!(parse_line("# comment").unwrap().is_none());
+    }
+
+    #[test]
+    fn malformed_lines_are_errors() {
+        assert!(parse_line("inc yesterday - today").is_err());
+        assert!(parse_line("inc 20261019T090000Z - ").is_err());
+        assert!(parse_line("inc 20261019T090000Z - 20261019T100000Z stray # acme").is_err());
+    }
+
+    #[test]
+    fn exported_lines_parse_back() {
+        let mut block = TimeBlock::new(utc("20261019T090000Z"), "Code".to_string(), "productive".to_string(), false);
+        block.end_time = utc("20261019T100000Z");
+        block.project = Some("time sense".to_string());
+        block.notes = vec![r###"fixed "##" parsing"###.to_string()];
+        let date = block.start_time.date_naive();
+
+        let line = export(&[(date, vec![block])]);
+        let parsed = interval(line.trim());
+        assert_eq!(parsed.tags, vec!["time sense", "productive"]);
+        assert_eq!(parsed.annotation.as_deref(), Some(r###"fixed "##" parsing"###));
+    }
+}